@@ -0,0 +1,92 @@
+// Variable lookups against the linear scan over a `Vec<Variable>` that used to do the
+// job, and a script that keeps a lot of variables around. Run with `cargo bench`
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use silm::{
+    interpreter::{interpret_source, Context, DataType, Variable},
+    stdio::MemoryIo,
+    variables::Variables,
+};
+
+fn time(name: &str, runs: u32, mut f: impl FnMut()) -> Duration {
+    let started = Instant::now();
+
+    for _ in 0..runs {
+        f();
+    }
+
+    let elapsed = started.elapsed() / runs;
+    println!("{:<40} {:>12?}", name, elapsed);
+    elapsed
+}
+
+fn lookups(count: usize) {
+    let names: Vec<String> = (0..count).map(|index| format!("v{}", index)).collect();
+    let list: Vec<Variable> = names
+        .iter()
+        .map(|name| Variable {
+            datatype: DataType::Int,
+            identifier: name.as_str().into(),
+            value: "1".into(),
+        })
+        .collect();
+    let variables: Variables = list.iter().cloned().collect();
+
+    let linear = time(&format!("linear scan, {} variables", count), 20, || {
+        for name in &names {
+            // What `get_variable` did before, clone included
+            black_box(
+                list.iter()
+                    .find(|variable| variable.identifier == *name)
+                    .cloned(),
+            );
+        }
+    });
+
+    let hashed = time(&format!("Variables::get, {} variables", count), 20, || {
+        for name in &names {
+            black_box(variables.get(name));
+        }
+    });
+
+    println!(
+        "{:<40} {:>11.1}x",
+        "speedup",
+        linear.as_secs_f64() / hashed.as_secs_f64()
+    );
+}
+
+fn script(count: usize) {
+    let mut source = String::new();
+
+    for index in 0..count {
+        source.push_str(&format!("let v{} = {}\n", index, index));
+    }
+    for index in 0..count {
+        source.push_str(&format!("let v{} = v{} + 1\n", index, index));
+    }
+
+    time(&format!("script, {} variables", count), 5, || {
+        let mut context = Context {
+            io: Box::new(MemoryIo::default()),
+            ..Default::default()
+        };
+        let mut variables = Variables::new();
+
+        interpret_source(&source, "bench".into(), &mut variables, &mut context);
+        black_box(variables.len());
+    });
+}
+
+fn main() {
+    for count in [10, 100, 1000, 5000] {
+        lookups(count);
+    }
+
+    for count in [100, 1000, 5000] {
+        script(count);
+    }
+}
@@ -0,0 +1,500 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    fs::{canonicalize, read_to_string},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    helper::{mismatched_types, snippet},
+    interpreter::{Context, DataType},
+    modules::resolve_path,
+    parser::{parse_source, Expression, Node, Span, Statement},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub input_name: String,
+    pub line_number: i32,
+    // Byte columns of the offending part of the line, `end` being exclusive
+    pub column: usize,
+    pub end: usize,
+    pub message: String,
+    // The offending line itself, shown under the message
+    pub source_line: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        // Line 0 is for problems with the file as a whole, like it not existing
+        if self.line_number == 0 {
+            write!(formatter, "{}: error: {}", self.input_name, self.message)
+        } else {
+            writeln!(
+                formatter,
+                "{}:{}:{}: error: {}",
+                self.input_name,
+                self.line_number,
+                self.column + 1,
+                self.message
+            )?;
+            write!(
+                formatter,
+                "{}",
+                snippet(&self.source_line, self.line_number, self.column, self.end).trim_end()
+            )
+        }
+    }
+}
+
+// Parses a file and every file it `interpret`s or `import`s with a literal path,
+// without running any of them
+pub fn check_file(path: &Path, lib_paths: &[PathBuf]) -> Vec<Diagnostic> {
+    check_files(&[path], lib_paths)
+}
+
+// `check_file` for several files at once, each file only checked and reported once
+// however many of them name it or import it
+pub fn check_files(paths: &[&Path], lib_paths: &[PathBuf]) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let mut checked: HashSet<PathBuf> = HashSet::new();
+
+    for path in paths {
+        check(path, lib_paths, &mut checked, &mut diagnostics);
+    }
+
+    diagnostics
+}
+
+fn check(
+    path: &Path,
+    lib_paths: &[PathBuf],
+    checked: &mut HashSet<PathBuf>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if let Ok(canonical_path) = canonicalize(path) {
+        if !checked.insert(canonical_path) {
+            return;
+        }
+    }
+
+    let input_name = path.display().to_string();
+
+    let source = match read_to_string(path) {
+        Ok(source) => source,
+
+        Err(error) => {
+            diagnostics.push(Diagnostic {
+                input_name,
+                line_number: 0,
+                column: 0,
+                end: 0,
+                message: error.to_string(),
+                source_line: String::new(),
+            });
+            return;
+        }
+    };
+
+    let mut targets: Vec<PathBuf> = Vec::new();
+
+    diagnostics.extend(check_lines(&source, path, lib_paths, &mut targets));
+
+    for target in targets {
+        check(&target, lib_paths, checked, diagnostics);
+    }
+}
+
+// Checks a single file's source, as it is in an editor which might not have saved it yet
+pub fn check_source(source: &str, path: &Path, lib_paths: &[PathBuf]) -> Vec<Diagnostic> {
+    check_lines(source, path, lib_paths, &mut Vec::new())
+}
+
+fn check_lines(
+    source: &str,
+    path: &Path,
+    lib_paths: &[PathBuf],
+    targets: &mut Vec<PathBuf>,
+) -> Vec<Diagnostic> {
+    let input_name = path.display().to_string();
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+    // Only used to resolve paths the same way `import` would from inside this file
+    let context = Context {
+        files: vec![path.to_path_buf()],
+        lib_paths: lib_paths.to_vec(),
+        ..Default::default()
+    };
+
+    let lines: Vec<&str> = source.lines().collect();
+    let mut types = Types::default();
+
+    for parsed_line in parse_source(source) {
+        let source_line = lines
+            .get(parsed_line.line_number as usize - 1)
+            .copied()
+            .unwrap_or("");
+
+        for error in parsed_line.errors {
+            diagnostics.push(Diagnostic {
+                input_name: input_name.clone(),
+                line_number: parsed_line.line_number,
+                column: error.column,
+                end: error.end,
+                message: error.message,
+                source_line: source_line.to_string(),
+            });
+        }
+
+        // Lines with syntax errors are `Statement::Invalid` and get skipped
+        let mut mismatches: Vec<(Span, String)> = Vec::new();
+        check_types(&parsed_line.node, &mut types, &mut mismatches);
+
+        for (span, message) in mismatches {
+            diagnostics.push(Diagnostic {
+                input_name: input_name.clone(),
+                line_number: parsed_line.line_number,
+                column: span.start,
+                end: span.end,
+                message,
+                source_line: source_line.to_string(),
+            });
+        }
+
+        let mut paths: Vec<&Expression> = Vec::new();
+        collect_paths(&parsed_line.node, &mut paths);
+
+        for expression in paths {
+            if let Expression::Literal(DataType::Str, filename, span) = expression {
+                match resolve_path(filename, &context) {
+                    Ok(target) => targets.push(target),
+
+                    Err(error) => diagnostics.push(Diagnostic {
+                        input_name: input_name.clone(),
+                        line_number: parsed_line.line_number,
+                        column: span.start,
+                        end: span.end,
+                        message: error,
+                        source_line: source_line.to_string(),
+                    }),
+                }
+            }
+        }
+    }
+
+    diagnostics
+}
+
+// Paths given to `interpret`, `import` and `from`, including inside bodies
+fn collect_paths<'a>(node: &'a Node, paths: &mut Vec<&'a Expression>) {
+    match &node.statement {
+        Statement::Interpret(path)
+        | Statement::Import { path, .. }
+        | Statement::From { path, .. } => paths.push(path),
+
+        Statement::Block { body, .. }
+        | Statement::If { body, .. }
+        | Statement::While { body, .. } => {
+            for node in body {
+                collect_paths(node, paths);
+            }
+        }
+
+        _ => {}
+    }
+}
+
+// Datatypes that are known without running anything, for what `interpret` would
+// stop with a mismatched types error
+#[derive(Debug, Clone, Default)]
+struct Types {
+    // From `let name: type` and typed parameters
+    declared: HashMap<String, DataType>,
+    // What variables were last given, as far as that can be told
+    known: HashMap<String, DataType>,
+    // Parameters of the blocks defined so far
+    blocks: HashMap<String, Vec<(String, Option<DataType>)>>,
+}
+
+impl Types {
+    fn datatype(&self, value: &Expression) -> Option<DataType> {
+        match value {
+            Expression::Literal(datatype, _, _) => Some(*datatype),
+
+            Expression::Variable(name, _) => match name.as_str() {
+                "$line_number$" => Some(DataType::Int),
+                "$version$" | "$line$" | "$input_name$" => Some(DataType::Str),
+                _ => self
+                    .declared
+                    .get(name)
+                    .or_else(|| self.known.get(name))
+                    .copied(),
+            },
+
+            Expression::Arithmetic(_, _) => Some(DataType::Float),
+
+            Expression::Call(name, _, _) => match name.as_str() {
+                "eq" | "ne" => Some(DataType::Bool),
+                "format" | "typeof" | "nameof" => Some(DataType::Str),
+                // Functions registered by an embedder could return anything
+                _ => None,
+            },
+        }
+    }
+}
+
+// Mirrors `helper::coerce`, ints being fine where floats are and floats where ints are,
+// since whether a float is whole is only known once it runs
+fn mismatch(name: &str, expected: DataType, got: Option<DataType>) -> Option<String> {
+    let fine = |got: DataType| {
+        got == expected
+            || (got == DataType::Int && expected == DataType::Float)
+            || (got == DataType::Float && expected == DataType::Int)
+    };
+
+    match got {
+        Some(got) if !fine(got) => Some(mismatched_types(name, expected, got)),
+        _ => None,
+    }
+}
+
+// `mismatch` for a value, where a float literal is already known to be whole or not
+fn mismatch_value(
+    name: &str,
+    expected: DataType,
+    got: Option<DataType>,
+    value: &Expression,
+) -> Option<String> {
+    let fractional = matches!(
+        value,
+        Expression::Literal(DataType::Float, text, _)
+            if text.parse::<f64>().map_or(true, |number| number.fract() != 0.0)
+    );
+
+    if fractional && got == Some(DataType::Float) && expected == DataType::Int {
+        Some(mismatched_types(name, expected, DataType::Float))
+    } else {
+        mismatch(name, expected, got)
+    }
+}
+
+fn check_types(node: &Node, types: &mut Types, mismatches: &mut Vec<(Span, String)>) {
+    match &node.statement {
+        Statement::Let {
+            name,
+            annotation,
+            value,
+            ..
+        } => {
+            let mut datatype = types.datatype(value);
+
+            for expected in [annotation.map(|(datatype, _)| datatype)]
+                .into_iter()
+                .chain([types.declared.get(name).copied()])
+                .flatten()
+            {
+                match mismatch_value(name, expected, datatype, value) {
+                    Some(message) => {
+                        mismatches.push((value.span(), message));
+                        // Nothing gets assigned when it fails
+                        return;
+                    }
+
+                    None => datatype = datatype.map(|_| expected),
+                }
+            }
+
+            if let Some((annotation, _)) = annotation {
+                types.declared.insert(name.clone(), *annotation);
+            }
+
+            match datatype {
+                Some(datatype) => types.known.insert(name.clone(), datatype),
+                None => types.known.remove(name),
+            };
+        }
+
+        Statement::Readln {
+            target: Expression::Variable(name, span),
+            ..
+        } => match types.declared.get(name) {
+            Some(expected) => {
+                if let Some(message) = mismatch(name, *expected, Some(DataType::Str)) {
+                    mismatches.push((*span, message));
+                }
+            }
+
+            None => {
+                types.known.insert(name.clone(), DataType::Str);
+            }
+        },
+
+        Statement::Block {
+            name,
+            name_span,
+            parameters,
+            body,
+        } => {
+            if let Some(expected) = types.declared.get(name) {
+                if let Some(message) = mismatch(name, *expected, Some(DataType::Block)) {
+                    mismatches.push((*name_span, message));
+                    return;
+                }
+            }
+
+            types.known.insert(name.clone(), DataType::Block);
+            types.blocks.insert(
+                name.clone(),
+                parameters
+                    .iter()
+                    .map(|(parameter, _, datatype)| (parameter.clone(), *datatype))
+                    .collect(),
+            );
+
+            // Blocks only start with their parameters
+            let mut block_types = Types::default();
+
+            for (parameter, _, datatype) in parameters {
+                if let Some(datatype) = datatype {
+                    block_types.declared.insert(parameter.clone(), *datatype);
+                }
+            }
+
+            for node in body {
+                check_types(node, &mut block_types, mismatches);
+            }
+        }
+
+        Statement::BlockCall(name, arguments, span) => {
+            if let Some(parameters) = types.blocks.get(name) {
+                if parameters.len() != arguments.len() {
+                    mismatches.push((
+                        *span,
+                        format!(
+                            "expected {} argument{}, got {}",
+                            parameters.len(),
+                            if parameters.len() == 1 { "" } else { "s" },
+                            arguments.len()
+                        ),
+                    ));
+                    return;
+                }
+
+                for ((parameter, expected), argument) in parameters.iter().zip(arguments) {
+                    if let Some(expected) = expected {
+                        if let Some(message) =
+                            mismatch_value(parameter, *expected, types.datatype(argument), argument)
+                        {
+                            mismatches.push((argument.span(), message));
+                        }
+                    }
+                }
+            }
+        }
+
+        // The body might not run, so only what it can't have changed is still known
+        Statement::If { body, .. } | Statement::While { body, .. } => {
+            let mut body_types = types.clone();
+
+            for node in body {
+                check_types(node, &mut body_types, mismatches);
+            }
+
+            types
+                .known
+                .retain(|name, datatype| body_types.known.get(name) == Some(datatype));
+            types
+                .blocks
+                .retain(|name, parameters| body_types.blocks.get(name) == Some(parameters));
+        }
+
+        // Anything could be defined by these
+        Statement::Interpret(_) | Statement::Eval(_) => {
+            types.known.clear();
+            types.blocks.clear();
+        }
+
+        Statement::From { names, .. } => {
+            for (name, _) in names {
+                types.known.remove(name);
+                types.blocks.remove(name);
+            }
+        }
+
+        _ => {}
+    }
+}
+
+#[test]
+fn test_check_types() {
+    let messages = |source: &str| -> Vec<(i32, usize, String)> {
+        check_source(source, Path::new("types.slm"), &[])
+            .into_iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.line_number,
+                    diagnostic.column,
+                    diagnostic.message,
+                )
+            })
+            .collect()
+    };
+
+    assert_eq!(
+        messages("let x: int = \"ten\""),
+        vec![(
+            1,
+            13,
+            "mismatched types: 'x' is declared as int, got str".to_string()
+        )]
+    );
+    assert_eq!(
+        messages("let x: float = 1\nlet y = x\nlet x = y\nlet x = eq (x, y)")[0].0,
+        4
+    );
+    assert_eq!(
+        messages("block greet (name: str) :: println (name)\ngreet (1)\ngreet (\"a\", 2)"),
+        vec![
+            (
+                2,
+                7,
+                "mismatched types: 'name' is declared as str, got int".to_string()
+            ),
+            (3, 0, "expected 1 argument, got 2".to_string())
+        ]
+    );
+
+    // Arithmetic gives a float, which is fine for an int as long as it's whole
+    assert!(messages("let i: int = 0\nlet i = i + 1\nlet z: int = 1 + 2").is_empty());
+    assert_eq!(
+        messages("let i: int = 2.5")[0].2,
+        "mismatched types: 'i' is declared as int, got float"
+    );
+
+    // Nothing is known about what `eval` or an `if` might have assigned
+    assert!(messages("let x: str = \"a\"\nlet y = 1\neval (\"let y = 2\")\nlet x = y").is_empty());
+    assert!(messages("let y = 1\nif (eq (y, 1)) :: let y = \"a\"\nlet x: int = y").is_empty());
+}
+
+#[test]
+fn test_check_file() {
+    let diagnostics = check_file(Path::new("examples/main.slm"), &[]);
+
+    // `^` isn't something the shunting yard algorithm understands
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].input_name, "examples/modules/calculate.slm");
+    assert_eq!(diagnostics[0].line_number, 3);
+    assert_eq!(diagnostics[0].column, 10);
+
+    assert!(check_file(Path::new("examples/login.slm"), &[]).is_empty());
+
+    // Given on its own as well as imported, it's still only reported once
+    let diagnostics = check_files(
+        &[
+            Path::new("examples/main.slm"),
+            Path::new("examples/modules/calculate.slm"),
+        ],
+        &[],
+    );
+    assert_eq!(diagnostics.len(), 1);
+}
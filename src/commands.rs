@@ -1,60 +1,170 @@
-use std::{
-    fs::read_to_string,
-    io::{self, Write},
-    process::exit,
-};
+use std::process::exit;
 
 use crate::{
     functions::silm_format,
-    helper::{assign, extract_data, throw_error},
-    interpreter::{interpret, DataType, Variable},
+    helper::{
+        assign, assign_constant, coerce, extract_data, joined, parse_datatype, represent_datatype,
+        split_arguments, throw_error, unknown_type, SPACED_COLON, UNSPACED_TYPE,
+    },
+    interpreter::{
+        interpret, interpret_file, interpret_part, parse_parameters, Context, DataType, FrameKind,
+        Variable,
+    },
+    modules::{load_module, module_name, resolve_path},
+    variables::Variables,
 };
 
 pub fn silm_let(
     tokens: Vec<&str>,
     input_name: String,
     line_number: i32,
-    variables: &mut Vec<Variable>,
+    variables: &mut Variables,
+    context: &mut Context,
 ) {
+    bind(tokens, "let", input_name, line_number, variables, context)
+}
+
+pub fn silm_const(
+    tokens: Vec<&str>,
+    input_name: String,
+    line_number: i32,
+    variables: &mut Variables,
+    context: &mut Context,
+) {
+    bind(tokens, "const", input_name, line_number, variables, context)
+}
+
+// `let` and `const`, which only differ in whether the name can be assigned again
+fn bind(
+    tokens: Vec<&str>,
+    command: &str,
+    input_name: String,
+    line_number: i32,
+    variables: &mut Variables,
+    context: &mut Context,
+) {
+    // `name: type = value` takes the type out, leaving `name = value`
+    let (tokens, annotation) = match tokens.first().and_then(|name| name.strip_suffix(':')) {
+        Some(name) if tokens.len() >= 2 => {
+            let mut rest = vec![name];
+            rest.extend_from_slice(&tokens[2..]);
+
+            (rest, Some(tokens[1]))
+        }
+
+        _ => (tokens, None),
+    };
+
+    // Written in a way that would otherwise give a variable a colon in its name
+    let misplaced = match tokens.first() {
+        Some(name) if name.contains(':') => name
+            .split_once(':')
+            .map(|(_, datatype)| (datatype, UNSPACED_TYPE)),
+        _ => tokens
+            .get(1)
+            .filter(|colon| colon.starts_with(':'))
+            .map(|colon| (&colon[..1], SPACED_COLON)),
+    };
+
+    if let Some((token, error)) = misplaced {
+        context.error_at = context.span_of(token);
+
+        return throw_error(error, command, input_name, line_number, context);
+    }
+
+    let datatype = match annotation {
+        Some(annotation) => match parse_datatype(annotation) {
+            Some(datatype) => Some(datatype),
+
+            None => {
+                context.error_at = context.span_of(annotation);
+
+                return throw_error(
+                    &unknown_type(annotation),
+                    command,
+                    input_name,
+                    line_number,
+                    context,
+                );
+            }
+        },
+
+        None => None,
+    };
+
     if tokens.len() >= 3 {
         if tokens[1] == "=" {
             let identifier = tokens[0].to_string();
-            let supposed_value = &mut tokens[2..].join(" ");
+            let text = context.text.clone();
+            let supposed_value = &joined(&tokens[2..], &text);
 
-            match extract_data(supposed_value, variables) {
+            match extract_data(supposed_value, variables, context) {
                 Ok(returned_variable_option) => {
                     if let Some(returned_variable) = returned_variable_option {
-                        let datatype = returned_variable.datatype;
-                        let value = returned_variable.value;
-
-                        assign(
-                            Variable {
-                                identifier,
-                                datatype,
-                                value,
-                            },
-                            variables,
-                        )
+                        let variable = Variable {
+                            identifier: identifier.as_str().into(),
+                            datatype: returned_variable.datatype,
+                            value: returned_variable.value,
+                        };
+
+                        // A value of the wrong type is pointed at, like `silm check` does,
+                        // and anything else about the name is pointed at the name
+                        let coerced = [datatype, variables.annotation(&identifier)]
+                            .into_iter()
+                            .flatten()
+                            .try_fold(variable, coerce)
+                            .map_err(|error| (error, context.span_of(supposed_value)));
+
+                        let assigned = coerced.and_then(|variable| {
+                            if command == "const" {
+                                assign_constant(variable, variables)
+                            } else {
+                                assign(variable, variables)
+                            }
+                            .map_err(|error| (error, context.span_of(tokens[0])))
+                        });
+
+                        match assigned {
+                            Ok(()) => {
+                                if let Some(datatype) = datatype {
+                                    variables.annotate(&identifier, datatype);
+                                }
+                            }
+
+                            Err((error, span)) => {
+                                context.error_at = span;
+
+                                throw_error(&error, command, input_name, line_number, context)
+                            }
+                        }
                     } else {
-                        throw_error("empty value given", "let", input_name, line_number)
+                        throw_error(
+                            "empty value given",
+                            command,
+                            input_name,
+                            line_number,
+                            context,
+                        )
                     }
                 }
-                Err(error) => throw_error(&error, "let", input_name, line_number),
+                Err(error) => throw_error(&error, command, input_name, line_number, context),
             }
         } else {
             throw_error(
                 "assignment operator (=) not found",
-                "let",
+                command,
                 input_name,
                 line_number,
+                context,
             );
         }
     } else {
         throw_error(
             "improper amount of tokens (!>= 4)",
-            "let",
+            command,
             input_name,
             line_number,
+            context,
         );
     }
 }
@@ -63,25 +173,26 @@ pub fn silm_println(
     tokens: Vec<&str>,
     input_name: String,
     line_number: i32,
-    variables: &[Variable],
+    variables: &Variables,
+    context: &mut Context,
 ) {
     if !tokens.is_empty() {
-        let argument = &mut tokens[0..].join(" ");
+        let text = context.text.clone();
+        let argument = &joined(&tokens[0..], &text);
 
         if argument.starts_with('(') && argument.ends_with(')') {
-            argument.remove(0);
-            argument.pop();
+            let argument = &argument[1..argument.len() - 1];
 
-            match extract_data(argument, variables) {
+            match extract_data(argument, variables, context) {
                 Ok(variable_option) => {
                     if let Some(variable) = variable_option {
-                        println!("{}", variable.value);
+                        let _ = writeln!(context.io.stdout(), "{}", variable.value);
                     } else {
-                        println!();
+                        let _ = writeln!(context.io.stdout());
                     }
                 }
                 Err(error) => {
-                    throw_error(&error, "println", input_name, line_number);
+                    throw_error(&error, "println", input_name, line_number, context);
                 }
             }
         } else {
@@ -90,10 +201,17 @@ pub fn silm_println(
                 "println",
                 input_name,
                 line_number,
+                context,
             );
         }
     } else {
-        throw_error("no argument provided", "println", input_name, line_number);
+        throw_error(
+            "no argument provided",
+            "println",
+            input_name,
+            line_number,
+            context,
+        );
     }
 }
 
@@ -101,15 +219,16 @@ pub fn silm_formatln(
     tokens: Vec<&str>,
     input_name: String,
     line_number: i32,
-    variables: &[Variable],
+    variables: &Variables,
+    context: &mut Context,
 ) {
-    match silm_format(tokens, variables) {
+    match silm_format(tokens, variables, context) {
         Ok(returned) => {
-            println!("{}", returned.value);
+            let _ = writeln!(context.io.stdout(), "{}", returned.value);
         }
 
         Err(error) => {
-            throw_error(&error, "formatln", input_name, line_number);
+            throw_error(&error, "formatln", input_name, line_number, context);
         }
     }
 }
@@ -118,39 +237,54 @@ pub fn silm_readln(
     tokens: Vec<&str>,
     input_name: String,
     line_number: i32,
-    variables: &mut Vec<Variable>,
+    variables: &mut Variables,
+    context: &mut Context,
 ) {
     if tokens.len() >= 2 {
-        let arguments = &mut tokens[0..].join(" ");
+        let text = context.text.clone();
+        let arguments = &joined(&tokens[0..], &text);
 
         if arguments.starts_with('(') && arguments.ends_with(')') {
-            arguments.remove(0);
-            arguments.pop();
+            let arguments = &arguments[1..arguments.len() - 1];
 
             match arguments.rsplit_once(',') {
                 Some((first_argument_str, second_argument_str)) => {
-                    match extract_data(first_argument_str, variables) {
+                    match extract_data(first_argument_str, variables, context) {
                         Ok(first_argument_option) => {
                             if let Some(first_argument) = first_argument_option {
-                                match extract_data(second_argument_str, variables) {
+                                match extract_data(second_argument_str, variables, context) {
                                     Ok(second_argument_option) => {
                                         if let Some(second_argument) = second_argument_option {
                                             if second_argument.datatype == DataType::Str {
-                                                print!("{}", first_argument.value);
-                                                io::stdout().flush().unwrap();
-
-                                                let mut stdin_text = String::new();
-                                                match io::stdin().read_line(&mut stdin_text) {
-                                                    Ok(_) => assign(
-                                                        Variable {
-                                                            datatype: second_argument.datatype,
-                                                            identifier: second_argument.identifier,
-                                                            value: stdin_text
-                                                                .trim_end()
-                                                                .to_string(),
-                                                        },
-                                                        variables,
-                                                    ),
+                                                let stdout = context.io.stdout();
+                                                let _ = write!(stdout, "{}", first_argument.value);
+                                                let _ = stdout.flush();
+
+                                                match context.io.read_line() {
+                                                    Ok(stdin_text) => {
+                                                        let assigned = assign(
+                                                            Variable {
+                                                                datatype: second_argument.datatype,
+                                                                identifier: second_argument
+                                                                    .identifier,
+                                                                value: stdin_text
+                                                                    .unwrap_or_default()
+                                                                    .trim_end()
+                                                                    .to_string(),
+                                                            },
+                                                            variables,
+                                                        );
+
+                                                        if let Err(error) = assigned {
+                                                            throw_error(
+                                                                &error,
+                                                                "readln",
+                                                                input_name,
+                                                                line_number,
+                                                                context,
+                                                            );
+                                                        }
+                                                    }
 
                                                     Err(_) => {
                                                         throw_error(
@@ -158,6 +292,7 @@ pub fn silm_readln(
                                                             "readln",
                                                             input_name,
                                                             line_number,
+                                                            context,
                                                         );
                                                     }
                                                 }
@@ -171,6 +306,7 @@ pub fn silm_readln(
                                                     "readln",
                                                     input_name,
                                                     line_number,
+                                                    context,
                                                 );
                                             }
                                         } else {
@@ -179,13 +315,18 @@ pub fn silm_readln(
                                                 "readln",
                                                 input_name,
                                                 line_number,
+                                                context,
                                             )
                                         }
                                     }
 
-                                    Err(error) => {
-                                        throw_error(&error, "readln", input_name, line_number)
-                                    }
+                                    Err(error) => throw_error(
+                                        &error,
+                                        "readln",
+                                        input_name,
+                                        line_number,
+                                        context,
+                                    ),
                                 }
                             } else {
                                 throw_error(
@@ -193,11 +334,14 @@ pub fn silm_readln(
                                     "readln",
                                     input_name,
                                     line_number,
+                                    context,
                                 )
                             }
                         }
 
-                        Err(error) => throw_error(&error, "readln", input_name, line_number),
+                        Err(error) => {
+                            throw_error(&error, "readln", input_name, line_number, context)
+                        }
                     }
                 }
 
@@ -206,6 +350,7 @@ pub fn silm_readln(
                     "readln",
                     input_name,
                     line_number,
+                    context,
                 ),
             }
         } else {
@@ -214,6 +359,7 @@ pub fn silm_readln(
                 "readln",
                 input_name,
                 line_number,
+                context,
             );
         }
     } else {
@@ -222,16 +368,29 @@ pub fn silm_readln(
             "readln",
             input_name,
             line_number,
+            context,
         );
     }
 }
 
-pub fn silm_exit(tokens: Vec<&str>, input_name: String, line_number: i32) {
+pub fn silm_exit(tokens: Vec<&str>, input_name: String, line_number: i32, context: &mut Context) {
     if !tokens.is_empty() {
         if tokens[0] == "()" {
+            // Nothing after this runs, including the end of `main`
+            if let Some(profiler) = &context.profiler {
+                profiler.finish();
+            }
+
+            let _ = context.io.stdout().flush();
             exit(0);
         } else {
-            throw_error("invalid command call", "exit", input_name, line_number);
+            throw_error(
+                "invalid command call",
+                "exit",
+                input_name,
+                line_number,
+                context,
+            );
         }
     } else {
         throw_error(
@@ -239,6 +398,7 @@ pub fn silm_exit(tokens: Vec<&str>, input_name: String, line_number: i32) {
             "exit",
             input_name,
             line_number,
+            context,
         )
     }
 }
@@ -247,30 +407,63 @@ pub fn silm_block(
     tokens: Vec<&str>,
     input_name: String,
     line_number: i32,
-    variables: &mut Vec<Variable>,
+    variables: &mut Variables,
+    context: &mut Context,
 ) {
     if !tokens.is_empty() {
         let name = tokens[0];
 
-        if tokens[1] == "::" {
-            let block_code = &tokens[2..].join(" ");
+        if let Some(colons) = tokens.iter().position(|token| *token == "::") {
+            let header = tokens[1..colons].join(" ");
+            let block_code = &tokens[colons + 1..].join(" ");
 
             let sections: Vec<&str> = block_code.split("\\;").collect();
 
-            assign(
-                Variable {
-                    identifier: name.to_string(),
-                    datatype: DataType::Block,
-                    value: sections.join("\n"),
-                },
-                variables,
-            )
+            // `block name (a: int, b) :: ...` keeps its parameters on the first line
+            let parameters = if header.is_empty() {
+                Ok(Vec::new())
+            } else {
+                match header
+                    .strip_prefix('(')
+                    .and_then(|header| header.strip_suffix(')'))
+                {
+                    Some(header) => parse_parameters(header),
+                    None => Err("parameters must be inside parantheses".to_string()),
+                }
+            };
+
+            let assigned = parameters.and_then(|parameters| {
+                let mut value = sections.join("\n");
+
+                if !parameters.is_empty() {
+                    let parameters: Vec<String> = parameters
+                        .iter()
+                        .map(|parameter| parameter.to_string())
+                        .collect();
+
+                    value = format!("({})\n{}", parameters.join(", "), value);
+                }
+
+                assign(
+                    Variable {
+                        identifier: name.into(),
+                        datatype: DataType::Block,
+                        value,
+                    },
+                    variables,
+                )
+            });
+
+            if let Err(error) = assigned {
+                throw_error(&error, "block", input_name, line_number, context)
+            }
         } else {
             throw_error(
                 "block must have two parantheses",
                 "block",
                 input_name,
                 line_number,
+                context,
             )
         }
     } else {
@@ -279,6 +472,7 @@ pub fn silm_block(
             "block",
             input_name,
             line_number,
+            context,
         )
     }
 }
@@ -287,42 +481,48 @@ pub fn silm_interpret(
     tokens: Vec<&str>,
     input_name: String,
     line_number: i32,
-    variables: &mut Vec<Variable>,
+    variables: &mut Variables,
+    context: &mut Context,
 ) {
     if !tokens.is_empty() {
-        let argument = &mut tokens[0..].join(" ");
+        let text = context.text.clone();
+        let argument = &joined(&tokens[0..], &text);
 
         if argument.starts_with('(') && argument.ends_with(')') {
-            argument.remove(0);
-            argument.pop();
+            let argument = &argument[1..argument.len() - 1];
 
-            match extract_data(argument, variables) {
+            match extract_data(argument, variables, context) {
                 Ok(variable_option) => {
                     if let Some(variable) = variable_option {
                         if variable.datatype == DataType::Str {
                             let filename = variable.value;
-                            match read_to_string(filename.clone()) {
-                                Ok(code) => {
-                                    let mut current_line = 0;
-                                    for line in code.lines() {
-                                        current_line += 1;
-                                        interpret(
-                                            line.to_string(),
-                                            filename.clone(),
-                                            current_line,
-                                            variables,
+
+                            match resolve_path(&filename, context) {
+                                Ok(path) => {
+                                    if let Err(error) = interpret_file(
+                                        &path,
+                                        filename,
+                                        FrameKind::Interpret,
+                                        variables,
+                                        context,
+                                    ) {
+                                        throw_error(
+                                            &format!("{}", error),
+                                            "interpret",
+                                            input_name,
+                                            line_number,
+                                            context,
                                         );
                                     }
                                 }
 
-                                Err(error) => {
-                                    throw_error(
-                                        &format!("{}", error),
-                                        "interpret",
-                                        input_name,
-                                        line_number,
-                                    );
-                                }
+                                Err(error) => throw_error(
+                                    &error,
+                                    "interpret",
+                                    input_name,
+                                    line_number,
+                                    context,
+                                ),
                             }
                         } else {
                             throw_error(
@@ -330,14 +530,15 @@ pub fn silm_interpret(
                                 "interpret",
                                 input_name,
                                 line_number,
+                                context,
                             );
                         }
                     } else {
-                        println!();
+                        let _ = writeln!(context.io.stdout());
                     }
                 }
                 Err(error) => {
-                    throw_error(&error, "interpret", input_name, line_number);
+                    throw_error(&error, "interpret", input_name, line_number, context);
                 }
             }
         } else {
@@ -346,10 +547,17 @@ pub fn silm_interpret(
                 "interpret",
                 input_name,
                 line_number,
+                context,
             );
         }
     } else {
-        throw_error("no argument provided", "interpret", input_name, line_number);
+        throw_error(
+            "no argument provided",
+            "interpret",
+            input_name,
+            line_number,
+            context,
+        );
     }
 }
 
@@ -357,22 +565,31 @@ pub fn silm_eval(
     tokens: Vec<&str>,
     input_name: String,
     line_number: i32,
-    variables: &mut Vec<Variable>,
+    variables: &mut Variables,
+    context: &mut Context,
 ) {
     if !tokens.is_empty() {
-        let argument = &mut tokens[0..].join(" ");
+        let text = context.text.clone();
+        let argument = &joined(&tokens[0..], &text);
 
         if argument.starts_with('(') && argument.ends_with(')') {
-            argument.remove(0);
-            argument.pop();
+            let argument = &argument[1..argument.len() - 1];
 
-            match extract_data(argument, variables) {
+            match extract_data(argument, variables, context) {
                 Ok(variable_option) => {
                     if let Some(variable) = variable_option {
                         if variable.datatype == DataType::Str {
-                            interpret(variable.value, input_name, line_number, variables);
+                            context.push_frame(FrameKind::Eval, &input_name, line_number);
+                            interpret(variable.value, input_name, line_number, variables, context);
+                            context.frames.pop();
                         } else {
-                            throw_error("argument must be a str", "eval", input_name, line_number);
+                            throw_error(
+                                "argument must be a str",
+                                "eval",
+                                input_name,
+                                line_number,
+                                context,
+                            );
                         }
                     } else {
                         throw_error(
@@ -380,11 +597,12 @@ pub fn silm_eval(
                             "eval",
                             input_name,
                             line_number,
+                            context,
                         );
                     }
                 }
                 Err(error) => {
-                    throw_error(&error, "eval", input_name, line_number);
+                    throw_error(&error, "eval", input_name, line_number, context);
                 }
             }
         } else {
@@ -393,10 +611,17 @@ pub fn silm_eval(
                 "eval",
                 input_name,
                 line_number,
+                context,
             );
         }
     } else {
-        throw_error("no argument provided", "eval", input_name, line_number);
+        throw_error(
+            "no argument provided",
+            "eval",
+            input_name,
+            line_number,
+            context,
+        );
     }
 }
 
@@ -404,55 +629,82 @@ pub fn silm_import(
     tokens: Vec<&str>,
     input_name: String,
     line_number: i32,
-    variables: &mut Vec<Variable>,
+    variables: &mut Variables,
+    context: &mut Context,
 ) {
     if !tokens.is_empty() {
-        let argument = &mut tokens[0..].join(" ");
+        let (argument_tokens, alias) = if tokens.len() >= 3 && tokens[tokens.len() - 2] == "as" {
+            (&tokens[..tokens.len() - 2], Some(tokens[tokens.len() - 1]))
+        } else {
+            (&tokens[..], None)
+        };
+
+        let text = context.text.clone();
+        let argument = &joined(argument_tokens, &text);
 
         if argument.starts_with('(') && argument.ends_with(')') {
-            argument.remove(0);
-            argument.pop();
+            let argument = &argument[1..argument.len() - 1];
 
-            match extract_data(argument, variables) {
+            match extract_data(argument, variables, context) {
                 Ok(variable_option) => {
                     if let Some(variable) = variable_option {
                         if variable.datatype == DataType::Str {
                             let filename = variable.value;
-                            match read_to_string(filename.clone()) {
-                                Ok(code) => {
-                                    let mut current_line = 0;
-                                    for line in code.lines() {
-                                        current_line += 1;
-                                        let code_tokens: Vec<&str> =
-                                            line.split_whitespace().collect();
-                                        if !code_tokens.is_empty() {
-                                            if code_tokens[0] == "let" {
-                                                silm_let(
-                                                    code_tokens[1..].to_vec(),
-                                                    filename.clone(),
-                                                    current_line,
-                                                    variables,
-                                                )
-                                            } else if code_tokens[0] == "block" {
-                                                silm_block(
-                                                    code_tokens[1..].to_vec(),
-                                                    filename.clone(),
-                                                    current_line,
-                                                    variables,
+
+                            match alias
+                                .map(|alias| alias.to_string())
+                                .or_else(|| module_name(&filename))
+                            {
+                                Some(namespace) => match load_module(&filename, context) {
+                                    Ok(module_variables) => {
+                                        for module_variable in &module_variables {
+                                            let namespaced_variable = Variable {
+                                                identifier: format!(
+                                                    "{}.{}",
+                                                    namespace, module_variable.identifier
                                                 )
+                                                .into(),
+                                                datatype: module_variable.datatype,
+                                                value: module_variable.value.clone(),
+                                            };
+
+                                            // Constants of the module stay constant
+                                            let assigned = if module_variables
+                                                .is_constant(&module_variable.identifier)
+                                            {
+                                                assign_constant(namespaced_variable, variables)
+                                            } else {
+                                                assign(namespaced_variable, variables)
+                                            };
+
+                                            if let Err(error) = assigned {
+                                                throw_error(
+                                                    &error,
+                                                    "import",
+                                                    input_name.clone(),
+                                                    line_number,
+                                                    context,
+                                                );
                                             }
                                         }
                                     }
-                                }
 
-                                Err(error) => {
-                                    throw_error(
-                                        &format!("{}", error),
+                                    Err(error) => throw_error(
+                                        &error,
                                         "import",
                                         input_name,
                                         line_number,
-                                    );
-                                }
+                                        context,
+                                    ),
+                                },
+
+                                None => throw_error(
+                                    "could not derive a module name, use `as` to name it",
+                                    "import",
+                                    input_name,
+                                    line_number,
+                                    context,
+                                ),
                             }
                         } else {
                             throw_error(
@@ -460,6 +712,7 @@ pub fn silm_import(
                                 "import",
                                 input_name,
                                 line_number,
+                                context,
                             );
                         }
                     } else {
@@ -468,11 +721,12 @@ pub fn silm_import(
                             "import",
                             input_name,
                             line_number,
+                            context,
                         );
                     }
                 }
                 Err(error) => {
-                    throw_error(&error, "import", input_name, line_number);
+                    throw_error(&error, "import", input_name, line_number, context);
                 }
             }
         } else {
@@ -481,10 +735,158 @@ pub fn silm_import(
                 "import",
                 input_name,
                 line_number,
+                context,
             );
         }
     } else {
-        throw_error("no argument provided", "import", input_name, line_number);
+        throw_error(
+            "no argument provided",
+            "import",
+            input_name,
+            line_number,
+            context,
+        );
+    }
+}
+
+pub fn silm_from(
+    tokens: Vec<&str>,
+    input_name: String,
+    line_number: i32,
+    variables: &mut Variables,
+    context: &mut Context,
+) {
+    if !tokens.is_empty() {
+        let text = context.text.clone();
+        let arguments = &joined(&tokens, &text);
+
+        match arguments.split_once(" import ") {
+            Some((module_str, names_str)) => {
+                let argument = module_str;
+
+                if argument.starts_with('(') && argument.ends_with(')') {
+                    let argument = &argument[1..argument.len() - 1];
+
+                    let names: Vec<&str> = names_str.split(',').map(|name| name.trim()).collect();
+
+                    if names.iter().all(|name| !name.is_empty()) {
+                        match extract_data(argument, variables, context) {
+                            Ok(Some(variable)) => {
+                                if variable.datatype == DataType::Str {
+                                    match load_module(&variable.value, context) {
+                                        Ok(module_variables) => {
+                                            for name in names {
+                                                match module_variables.iter().find(
+                                                    |module_variable| {
+                                                        module_variable.identifier == name
+                                                    },
+                                                ) {
+                                                    Some(module_variable) => {
+                                                        let assigned =
+                                                            if module_variables.is_constant(name) {
+                                                                assign_constant(
+                                                                    module_variable.clone(),
+                                                                    variables,
+                                                                )
+                                                            } else {
+                                                                assign(
+                                                                    module_variable.clone(),
+                                                                    variables,
+                                                                )
+                                                            };
+
+                                                        if let Err(error) = assigned {
+                                                            throw_error(
+                                                                &error,
+                                                                "from",
+                                                                input_name.clone(),
+                                                                line_number,
+                                                                context,
+                                                            );
+                                                        }
+                                                    }
+
+                                                    None => throw_error(
+                                                        &format!(
+                                                            "'{}' is not defined in {}",
+                                                            name, variable.value
+                                                        ),
+                                                        "from",
+                                                        input_name.clone(),
+                                                        line_number,
+                                                        context,
+                                                    ),
+                                                }
+                                            }
+                                        }
+
+                                        Err(error) => throw_error(
+                                            &error,
+                                            "from",
+                                            input_name,
+                                            line_number,
+                                            context,
+                                        ),
+                                    }
+                                } else {
+                                    throw_error(
+                                        "module must be a str",
+                                        "from",
+                                        input_name,
+                                        line_number,
+                                        context,
+                                    );
+                                }
+                            }
+
+                            Ok(None) => throw_error(
+                                "no module provided",
+                                "from",
+                                input_name,
+                                line_number,
+                                context,
+                            ),
+
+                            Err(error) => {
+                                throw_error(&error, "from", input_name, line_number, context)
+                            }
+                        }
+                    } else {
+                        throw_error(
+                            "empty name in import list",
+                            "from",
+                            input_name,
+                            line_number,
+                            context,
+                        );
+                    }
+                } else {
+                    throw_error(
+                        "module must be given in two parantheses",
+                        "from",
+                        input_name,
+                        line_number,
+                        context,
+                    );
+                }
+            }
+
+            None => throw_error(
+                "no `import` list provided",
+                "from",
+                input_name,
+                line_number,
+                context,
+            ),
+        }
+    } else {
+        throw_error(
+            "no module provided",
+            "from",
+            input_name,
+            line_number,
+            context,
+        );
     }
 }
 
@@ -492,19 +894,20 @@ pub fn silm_if(
     tokens: Vec<&str>,
     input_name: String,
     line_number: i32,
-    variables: &mut Vec<Variable>,
+    variables: &mut Variables,
+    context: &mut Context,
 ) {
     if !tokens.is_empty() {
-        let arguments = &tokens[0..].join(" ");
+        let text = context.text.clone();
+        let arguments = &joined(&tokens, &text);
         match arguments.split_once(" :: ") {
             Some((condition_str, then_str)) => {
-                let mut condition = String::from(condition_str);
+                let condition = condition_str;
 
                 if condition.starts_with('(') && condition.ends_with(')') {
-                    condition.remove(0);
-                    condition.pop();
+                    let condition = &condition[1..condition.len() - 1];
 
-                    match extract_data(&condition, variables) {
+                    match extract_data(condition, variables, context) {
                         Ok(result_option) => {
                             if let Some(result) = result_option {
                                 if result.datatype == DataType::Bool {
@@ -512,11 +915,12 @@ pub fn silm_if(
                                         let then: Vec<&str> = then_str.split("\\;").collect();
 
                                         for line in then {
-                                            interpret(
-                                                line.into(),
+                                            interpret_part(
+                                                line,
                                                 input_name.clone(),
                                                 line_number,
                                                 variables,
+                                                context,
                                             );
                                         }
                                     }
@@ -526,13 +930,14 @@ pub fn silm_if(
                                         "if",
                                         input_name,
                                         line_number,
+                                        context,
                                     );
                                 }
                             }
                         }
 
                         Err(error) => {
-                            throw_error(&error, "if", input_name, line_number);
+                            throw_error(&error, "if", input_name, line_number, context);
                         }
                     }
                 } else {
@@ -541,6 +946,7 @@ pub fn silm_if(
                         "if",
                         input_name,
                         line_number,
+                        context,
                     );
                 }
             }
@@ -550,11 +956,18 @@ pub fn silm_if(
                     "if",
                     input_name,
                     line_number,
+                    context,
                 );
             }
         }
     } else {
-        throw_error("no condition provided", "if", input_name, line_number);
+        throw_error(
+            "no condition provided",
+            "if",
+            input_name,
+            line_number,
+            context,
+        );
     }
 }
 
@@ -562,49 +975,70 @@ pub fn silm_while(
     tokens: Vec<&str>,
     input_name: String,
     line_number: i32,
-    variables: &mut Vec<Variable>,
+    variables: &mut Variables,
+    context: &mut Context,
 ) {
     if !tokens.is_empty() {
-        let arguments = &tokens[0..].join(" ");
+        let text = context.text.clone();
+        let arguments = &joined(&tokens, &text);
         match arguments.split_once(" :: ") {
             Some((condition_str, then_str)) => {
-                let mut condition = String::from(condition_str);
+                let condition = condition_str;
 
                 if condition.starts_with('(') && condition.ends_with(')') {
-                    condition.remove(0);
-                    condition.pop();
+                    let condition = &condition[1..condition.len() - 1];
 
-                    match extract_data(&condition, variables) {
-                        Ok(result_option) => {
-                            if let Some(result) = result_option {
-                                if result.datatype == DataType::Bool {
-                                    if result.value == "true" {
-                                        let then: Vec<&str> = then_str.split("\\;").collect();
+                    let then: Vec<&str> = then_str.split("\\;").collect();
 
-                                        for line in then {
-                                            interpret(
-                                                line.into(),
-                                                input_name.clone(),
-                                                line_number,
-                                                variables,
-                                            );
-                                        }
+                    // A loop rather than recursion, so long loops don't overflow the stack
+                    while !context.stopped {
+                        if let Err(error) = context.step() {
+                            throw_error(&error, "while", input_name.clone(), line_number, context);
+                            context.stopped = true;
+                            break;
+                        }
 
-                                        silm_while(tokens, input_name, line_number, variables);
-                                    }
-                                } else {
+                        match extract_data(condition, variables, context) {
+                            Ok(Some(result)) => {
+                                if result.datatype != DataType::Bool {
                                     throw_error(
                                         "condition must be of type bool",
                                         "while",
-                                        input_name,
+                                        input_name.clone(),
                                         line_number,
+                                        context,
+                                    );
+
+                                    break;
+                                }
+
+                                if result.value != "true" {
+                                    break;
+                                }
+
+                                for line in &then {
+                                    interpret_part(
+                                        line,
+                                        input_name.clone(),
+                                        line_number,
+                                        variables,
+                                        context,
                                     );
                                 }
                             }
-                        }
 
-                        Err(error) => {
-                            throw_error(&error, "while", input_name, line_number);
+                            Ok(None) => break,
+
+                            Err(error) => {
+                                throw_error(
+                                    &error,
+                                    "while",
+                                    input_name.clone(),
+                                    line_number,
+                                    context,
+                                );
+                                break;
+                            }
                         }
                     }
                 } else {
@@ -613,6 +1047,7 @@ pub fn silm_while(
                         "while",
                         input_name,
                         line_number,
+                        context,
                     );
                 }
             }
@@ -622,10 +1057,325 @@ pub fn silm_while(
                     "while",
                     input_name,
                     line_number,
+                    context,
                 );
             }
         }
     } else {
-        throw_error("no condition provided", "while", input_name, line_number);
+        throw_error(
+            "no condition provided",
+            "while",
+            input_name,
+            line_number,
+            context,
+        );
+    }
+}
+
+pub fn silm_assert(
+    tokens: Vec<&str>,
+    input_name: String,
+    line_number: i32,
+    variables: &Variables,
+    context: &mut Context,
+) {
+    if !tokens.is_empty() {
+        let text = context.text.clone();
+        let arguments = &joined(&tokens[0..], &text);
+
+        if arguments.starts_with('(') && arguments.ends_with(')') {
+            let arguments = &arguments[1..arguments.len() - 1];
+
+            let arguments = split_arguments(arguments);
+
+            if arguments.len() == 1 || arguments.len() == 2 {
+                let condition_str = arguments[0];
+
+                match extract_data(condition_str, variables, context) {
+                    Ok(Some(condition)) => {
+                        if condition.datatype == DataType::Bool {
+                            if condition.value != "true" {
+                                match assertion_message(arguments.get(1), variables, context) {
+                                    Ok(Some(message)) => throw_error(
+                                        &format!("assertion failed: {}", message),
+                                        "assert",
+                                        input_name,
+                                        line_number,
+                                        context,
+                                    ),
+
+                                    Ok(None) => throw_error(
+                                        &format!("assertion failed: `{}`", condition_str),
+                                        "assert",
+                                        input_name,
+                                        line_number,
+                                        context,
+                                    ),
+
+                                    Err(error) => throw_error(
+                                        &error,
+                                        "assert",
+                                        input_name,
+                                        line_number,
+                                        context,
+                                    ),
+                                }
+                            }
+                        } else {
+                            throw_error(
+                                "condition must be of type bool",
+                                "assert",
+                                input_name,
+                                line_number,
+                                context,
+                            );
+                        }
+                    }
+
+                    Ok(None) => throw_error(
+                        "no condition provided",
+                        "assert",
+                        input_name,
+                        line_number,
+                        context,
+                    ),
+
+                    Err(error) => throw_error(&error, "assert", input_name, line_number, context),
+                }
+            } else {
+                throw_error(
+                    "command requires a condition and an optional message",
+                    "assert",
+                    input_name,
+                    line_number,
+                    context,
+                );
+            }
+        } else {
+            throw_error(
+                "command call does not contain two parantheses",
+                "assert",
+                input_name,
+                line_number,
+                context,
+            );
+        }
+    } else {
+        throw_error(
+            "no argument provided",
+            "assert",
+            input_name,
+            line_number,
+            context,
+        );
+    }
+}
+
+#[test]
+fn test_silm_assert() {
+    use crate::stdio::MemoryIo;
+
+    let io = MemoryIo::new("");
+    let mut context = Context {
+        io: Box::new(io.clone()),
+        ..Default::default()
+    };
+    let variables = Variables::new();
+
+    silm_assert(
+        vec!["(eq", "(1,", "1))"],
+        "a.slm".into(),
+        1,
+        &variables,
+        &mut context,
+    );
+    assert_eq!(context.errors, 0);
+
+    silm_assert(
+        vec!["(eq", "(1,", "2),", "\"one", "is", "two\")"],
+        "a.slm".into(),
+        2,
+        &variables,
+        &mut context,
+    );
+    assert_eq!(context.errors, 1);
+    assert!(io
+        .stdout_text()
+        .starts_with("error at a.slm:2: assert: assertion failed: one is two\n"));
+
+    silm_assert(vec!["(1)"], "a.slm".into(), 3, &variables, &mut context);
+    assert_eq!(context.errors, 2);
+}
+
+pub fn silm_assert_eq(
+    tokens: Vec<&str>,
+    input_name: String,
+    line_number: i32,
+    variables: &Variables,
+    context: &mut Context,
+) {
+    if !tokens.is_empty() {
+        let text = context.text.clone();
+        let arguments = &joined(&tokens[0..], &text);
+
+        if arguments.starts_with('(') && arguments.ends_with(')') {
+            let arguments = &arguments[1..arguments.len() - 1];
+
+            let arguments = split_arguments(arguments);
+
+            if arguments.len() == 2 || arguments.len() == 3 {
+                match extract_data(arguments[0], variables, context) {
+                    Ok(Some(left)) => match extract_data(arguments[1], variables, context) {
+                        Ok(Some(right)) => {
+                            if left.datatype != right.datatype || left.value != right.value {
+                                let comparison = format!(
+                                    "`{} == {}` (left: {} ({}), right: {} ({}))",
+                                    arguments[0],
+                                    arguments[1],
+                                    left.value,
+                                    represent_datatype(left.datatype),
+                                    right.value,
+                                    represent_datatype(right.datatype)
+                                );
+
+                                match assertion_message(arguments.get(2), variables, context) {
+                                    Ok(Some(message)) => throw_error(
+                                        &format!("assertion failed: {}: {}", message, comparison),
+                                        "assert_eq",
+                                        input_name,
+                                        line_number,
+                                        context,
+                                    ),
+
+                                    Ok(None) => throw_error(
+                                        &format!("assertion failed: {}", comparison),
+                                        "assert_eq",
+                                        input_name,
+                                        line_number,
+                                        context,
+                                    ),
+
+                                    Err(error) => throw_error(
+                                        &error,
+                                        "assert_eq",
+                                        input_name,
+                                        line_number,
+                                        context,
+                                    ),
+                                }
+                            }
+                        }
+
+                        Ok(None) => throw_error(
+                            "second argument not given",
+                            "assert_eq",
+                            input_name,
+                            line_number,
+                            context,
+                        ),
+
+                        Err(error) => {
+                            throw_error(&error, "assert_eq", input_name, line_number, context)
+                        }
+                    },
+
+                    Ok(None) => throw_error(
+                        "first argument not given",
+                        "assert_eq",
+                        input_name,
+                        line_number,
+                        context,
+                    ),
+
+                    Err(error) => {
+                        throw_error(&error, "assert_eq", input_name, line_number, context)
+                    }
+                }
+            } else {
+                throw_error(
+                    "command requires two values and an optional message",
+                    "assert_eq",
+                    input_name,
+                    line_number,
+                    context,
+                );
+            }
+        } else {
+            throw_error(
+                "command call does not contain two parantheses",
+                "assert_eq",
+                input_name,
+                line_number,
+                context,
+            );
+        }
+    } else {
+        throw_error(
+            "no argument provided",
+            "assert_eq",
+            input_name,
+            line_number,
+            context,
+        );
+    }
+}
+
+#[test]
+fn test_silm_assert_eq() {
+    use crate::stdio::MemoryIo;
+
+    let io = MemoryIo::new("");
+    let mut context = Context {
+        io: Box::new(io.clone()),
+        ..Default::default()
+    };
+    let variables = Variables::new();
+
+    silm_assert_eq(
+        vec!["(2,", "2)"],
+        "a.slm".into(),
+        1,
+        &variables,
+        &mut context,
+    );
+    assert_eq!(context.errors, 0);
+
+    silm_assert_eq(
+        vec!["(1,", "1.0)"],
+        "a.slm".into(),
+        2,
+        &variables,
+        &mut context,
+    );
+    assert_eq!(context.errors, 1);
+    assert!(io.stdout_text().starts_with(
+        "error at a.slm:2: assert_eq: assertion failed: `1 == 1.0` (left: 1 (int), right: 1 (float))\n"
+    ));
+
+    silm_assert_eq(vec!["(1)"], "a.slm".into(), 3, &variables, &mut context);
+    assert_eq!(context.errors, 2);
+}
+
+fn assertion_message(
+    argument: Option<&&str>,
+    variables: &Variables,
+    context: &mut Context,
+) -> Result<Option<String>, String> {
+    match argument {
+        Some(argument) => match extract_data(argument, variables, context) {
+            Ok(Some(message)) => {
+                if message.datatype == DataType::Str {
+                    Ok(Some(message.value))
+                } else {
+                    Err("message must be a str".into())
+                }
+            }
+
+            Ok(None) => Err("empty message given".into()),
+
+            Err(error) => Err(error),
+        },
+
+        None => Ok(None),
     }
 }
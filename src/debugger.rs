@@ -0,0 +1,323 @@
+use std::process::exit;
+
+use crate::{
+    helper::{extract_data, magic_variable, represent_value, MAGIC_VARIABLES},
+    interpreter::{Context, Frame, Variable},
+    variables::Variables,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Mode {
+    // Stop before the next line wherever it is
+    Step,
+    // Stop before the next line that isn't deeper than this many frames
+    Next(usize),
+    // Only stop at breakpoints
+    Continue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Breakpoint {
+    // `None` means the file being debugged
+    file: Option<String>,
+    line_number: i32,
+}
+
+// `silm debug`, which stops before lines of files and blocks and asks what to do
+#[derive(Debug)]
+pub struct Debugger {
+    mode: Mode,
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl Default for Debugger {
+    fn default() -> Self {
+        Debugger {
+            mode: Mode::Step,
+            breakpoints: Vec::new(),
+        }
+    }
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Called before every line of a file or block, prompts for commands until told to go on
+    pub fn pause(&mut self, line: &str, variables: &Variables, context: &mut Context) {
+        let frame = match context.frames.last() {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        // Nothing happens on these lines so there's nothing to stop for
+        if line.trim().is_empty() || line.trim().starts_with('#') {
+            return;
+        }
+
+        if !self.should_stop(frame, &context.frames) {
+            return;
+        }
+
+        let stopped_at = format!(
+            "-> {}:{}: {}",
+            frame.input_name,
+            frame.line_number,
+            line.trim()
+        );
+        let _ = writeln!(context.io.stdout(), "{}", stopped_at);
+
+        loop {
+            let stdout = context.io.stdout();
+            let _ = write!(stdout, "(silm) ");
+            let _ = stdout.flush();
+
+            match context.io.read_line() {
+                Ok(None) | Err(_) => {
+                    // Nobody's left to answer, so let the script run to its end
+                    let _ = writeln!(context.io.stdout());
+                    self.mode = Mode::Continue;
+                    self.breakpoints.clear();
+                    return;
+                }
+
+                Ok(Some(input)) => {
+                    if self.command(input.trim(), variables, context) {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    fn should_stop(&self, frame: &Frame, frames: &[Frame]) -> bool {
+        let at_breakpoint = self.breakpoints.iter().any(|breakpoint| {
+            breakpoint.line_number == frame.line_number
+                && match &breakpoint.file {
+                    Some(file) => {
+                        frame.input_name == *file
+                            || frame.input_name.ends_with(&format!("/{}", file))
+                            || frame.input_name == format!("<block {}>", file)
+                    }
+
+                    None => frames
+                        .first()
+                        .is_some_and(|first| first.input_name == frame.input_name),
+                }
+        });
+
+        match self.mode {
+            Mode::Step => true,
+            Mode::Next(depth) => frames.len() <= depth || at_breakpoint,
+            Mode::Continue => at_breakpoint,
+        }
+    }
+
+    // Runs one debugger command, returning whether the script should go on
+    fn command(&mut self, input: &str, variables: &Variables, context: &mut Context) -> bool {
+        let (command, argument) = match input.split_once(' ') {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (input, ""),
+        };
+
+        let mut output = String::new();
+
+        match command {
+            "s" | "step" => {
+                self.mode = Mode::Step;
+                return true;
+            }
+
+            "n" | "next" => {
+                self.mode = Mode::Next(context.frames.len());
+                return true;
+            }
+
+            "c" | "continue" => {
+                self.mode = Mode::Continue;
+                return true;
+            }
+
+            "b" | "break" => {
+                if argument.is_empty() {
+                    if self.breakpoints.is_empty() {
+                        output.push_str("no breakpoints\n");
+                    }
+
+                    for breakpoint in &self.breakpoints {
+                        match &breakpoint.file {
+                            Some(file) => {
+                                output += &format!("{}:{}\n", file, breakpoint.line_number)
+                            }
+                            None => output += &format!("{}\n", breakpoint.line_number),
+                        }
+                    }
+                } else {
+                    match parse_breakpoint(argument) {
+                        Some(breakpoint) => {
+                            if !self.breakpoints.contains(&breakpoint) {
+                                self.breakpoints.push(breakpoint);
+                            }
+                        }
+
+                        None => output.push_str("break requires a line like 12 or file.slm:12\n"),
+                    }
+                }
+            }
+
+            "d" | "delete" => match parse_breakpoint(argument) {
+                Some(breakpoint) => {
+                    let count = self.breakpoints.len();
+                    self.breakpoints.retain(|existing| *existing != breakpoint);
+
+                    if self.breakpoints.len() == count {
+                        output += &format!("no breakpoint at {}\n", argument);
+                    }
+                }
+
+                None => output.push_str("delete requires a line like 12 or file.slm:12\n"),
+            },
+
+            "p" | "print" => match extract_data(argument, variables, context) {
+                Ok(Some(variable)) => output += &format!("{}\n", represent_value(&variable)),
+                Ok(None) => output.push_str("print requires an expression\n"),
+                Err(error) => output += &format!("error: {}\n", error),
+            },
+
+            "vars" => {
+                let show_magic = argument == "all";
+
+                for variable in variables {
+                    if show_magic || !variable.identifier.starts_with('$') {
+                        output += &describe(variable);
+                    }
+                }
+
+                if show_magic {
+                    for variable in MAGIC_VARIABLES
+                        .iter()
+                        .filter_map(|name| magic_variable(name, context))
+                    {
+                        output += &describe(&variable);
+                    }
+                }
+            }
+
+            "bt" | "backtrace" => {
+                for (index, frame) in context.frames.iter().rev().enumerate() {
+                    output += &format!(
+                        "#{} {}:{} ({})\n",
+                        index, frame.input_name, frame.line_number, frame.kind
+                    );
+                }
+            }
+
+            "q" | "quit" => exit(0),
+
+            "h" | "help" => output.push_str(
+                r#"s, step              Run the line, stopping inside blocks and files it runs
+n, next              Run the line without stopping inside blocks and files
+c, continue          Run until the next breakpoint
+b, break [file:]line Stop before a line, or list breakpoints without one
+d, delete [file:]line
+                     Remove a breakpoint
+p, print <expr>      Show the value of a variable or expression
+vars [all]           Show every variable in the current scope, `all` includes
+                     the $magic$ ones
+bt, backtrace        Show the files, blocks and evals being run
+q, quit              Stop the script and the debugger
+"#,
+            ),
+
+            "" => {}
+
+            _ => output += &format!("unknown command: {}, see help\n", command),
+        }
+
+        let _ = write!(context.io.stdout(), "{}", output);
+
+        false
+    }
+}
+
+#[test]
+fn test_command() {
+    use crate::{interpreter::FrameKind, stdio::MemoryIo};
+
+    let mut debugger = Debugger::new();
+    let frames = vec![
+        Frame {
+            kind: FrameKind::File,
+            input_name: "main.slm".into(),
+            line_number: 4,
+        },
+        Frame {
+            kind: FrameKind::Block,
+            input_name: "<block greet>".into(),
+            line_number: 1,
+        },
+    ];
+
+    let io = MemoryIo::new("");
+    let mut context = Context {
+        frames: frames.clone(),
+        io: Box::new(io.clone()),
+        ..Default::default()
+    };
+
+    assert!(!debugger.command("break", &Variables::new(), &mut context));
+    assert!(!debugger.command("break 7", &Variables::new(), &mut context));
+    assert!(!debugger.command("b greet:2", &Variables::new(), &mut context));
+    assert!(!debugger.command("break", &Variables::new(), &mut context));
+    assert!(!debugger.command("p \"hi\"", &Variables::new(), &mut context));
+    assert_eq!(
+        io.stdout_text(),
+        "no breakpoints\n7\ngreet:2\n\"hi\" (str)\n"
+    );
+
+    assert!(debugger.command("continue", &Variables::new(), &mut context));
+
+    assert!(!debugger.should_stop(&frames[1], &frames));
+    assert!(debugger.should_stop(
+        &Frame {
+            kind: FrameKind::Block,
+            input_name: "<block greet>".into(),
+            line_number: 2,
+        },
+        &frames
+    ));
+    assert!(debugger.should_stop(
+        &Frame {
+            kind: FrameKind::File,
+            input_name: "main.slm".into(),
+            line_number: 7,
+        },
+        &frames[..1]
+    ));
+
+    // Stepping over the block call stops at the next line of the file, not inside the block
+    context.frames.pop();
+    assert!(debugger.command("next", &Variables::new(), &mut context));
+    assert!(!debugger.should_stop(&frames[1], &frames));
+    assert!(debugger.should_stop(&frames[0], &frames[..1]));
+}
+
+fn parse_breakpoint(argument: &str) -> Option<Breakpoint> {
+    match argument.rsplit_once(':') {
+        Some((file, line_number)) => Some(Breakpoint {
+            file: Some(file.to_string()),
+            line_number: line_number.parse().ok()?,
+        }),
+
+        None => Some(Breakpoint {
+            file: None,
+            line_number: argument.parse().ok()?,
+        }),
+    }
+}
+
+// A line of `vars`
+fn describe(variable: &Variable) -> String {
+    format!("{} = {}\n", variable.identifier, represent_value(variable))
+}
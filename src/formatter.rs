@@ -0,0 +1,145 @@
+use crate::{
+    lexer::{tokenize, Token, TokenKind},
+    parser::parse_source,
+};
+
+// Rewrites a whole file into the canonical layout: one space around `=`, `::`, `\;`
+// and operators, a space after commas, `name (arguments)` for calls and spaced out
+// parantheses in arithmetic. Comments are kept as they are and lines with syntax
+// errors are left alone, so formatting never changes what a script does
+pub fn format_source(source: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in source.lines() {
+        let formatted = format_line(line);
+
+        // Never more than one blank line in a row, and none at the start
+        if formatted.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+
+        lines.push(formatted);
+    }
+
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+
+    if lines.is_empty() {
+        String::new()
+    } else {
+        lines.join("\n") + "\n"
+    }
+}
+
+#[test]
+fn test_format_source() {
+    assert_eq!(
+        format_source(
+            "\n\n# A comment  \nlet   x =  ( 1+2 )\n\n\nif (eq(x,3))::println ( x )\\;exit ()\n\n"
+        ),
+        "# A comment\nlet   x =  ( 1+2 )\n\nif (eq(x,3))::println ( x )\\;exit ()\n"
+    );
+
+    assert_eq!(
+        format_source(
+            "let  y = (  x  +  2 )  *  -3\nif ( eq (x,  3) ) :: println ( x )  \\;  exit ()\n"
+        ),
+        "let y = ( x + 2 ) * -3\nif (eq (x, 3)) :: println (x) \\; exit ()\n"
+    );
+
+    assert_eq!(
+        format_source("block  greet  ::  readln (\"name?  \" ,name)\\;greet ()\n"),
+        "block greet :: readln (\"name?  \", name) \\; greet ()\n"
+    );
+
+    assert_eq!(
+        format_source("let  x: int =  1\nblock  greet  (name :str ,n)  ::  println (name)\n"),
+        "let x: int = 1\nblock greet (name: str, n) :: println (name)\n"
+    );
+
+    let formatted = format_source("from (\"a.slm\")  import  a ,b\nprintln (( 1 + 2 ))\n");
+    assert_eq!(
+        formatted,
+        "from (\"a.slm\") import a, b\nprintln (( 1 + 2 ))\n"
+    );
+    assert_eq!(format_source(&formatted), formatted);
+}
+
+pub fn format_line(line: &str) -> String {
+    let trimmed = line.trim();
+
+    let tokens = match tokenize(trimmed) {
+        Ok(tokens) => tokens,
+        Err(_) => return line.trim_end().to_string(),
+    };
+
+    if !parse_source(trimmed)
+        .iter()
+        .all(|parsed_line| parsed_line.errors.is_empty())
+    {
+        return line.trim_end().to_string();
+    }
+
+    let formatted = join_tokens(&tokens);
+
+    // Only trust the new layout if it still reads as the same tokens without errors
+    let unchanged = match tokenize(&formatted) {
+        Ok(new_tokens) => {
+            new_tokens.len() == tokens.len()
+                && new_tokens
+                    .iter()
+                    .zip(&tokens)
+                    .all(|(new, old)| new.kind == old.kind && new.text == old.text)
+                && parse_source(&formatted)
+                    .iter()
+                    .all(|parsed_line| parsed_line.errors.is_empty())
+        }
+        Err(_) => false,
+    };
+
+    if unchanged {
+        formatted
+    } else {
+        line.trim_end().to_string()
+    }
+}
+
+fn join_tokens(tokens: &[Token]) -> String {
+    let mut result = String::new();
+    // Whether each open paranthesis belongs to a call (`name (`) or groups arithmetic
+    let mut call_parens: Vec<bool> = Vec::new();
+
+    for (index, token) in tokens.iter().enumerate() {
+        let previous = if index > 0 {
+            Some(&tokens[index - 1])
+        } else {
+            None
+        };
+
+        let space = match (previous, token.kind) {
+            (None, _) => false,
+            (_, TokenKind::Comma | TokenKind::Colon) => false,
+            (Some(previous), TokenKind::RightParen) => {
+                let is_call = call_parens.pop().unwrap_or(true);
+                !is_call && previous.kind != TokenKind::LeftParen
+            }
+            (Some(previous), _) if previous.kind == TokenKind::LeftParen => {
+                !call_parens.last().copied().unwrap_or(true)
+            }
+            _ => true,
+        };
+
+        if token.kind == TokenKind::LeftParen {
+            call_parens.push(previous.is_some_and(|previous| previous.kind == TokenKind::Word));
+        }
+
+        if space {
+            result.push(' ');
+        }
+
+        result.push_str(token.text);
+    }
+
+    result
+}
@@ -1,21 +1,30 @@
 use crate::{
-    helper::{extract_data, get_variable, represent_datatype},
-    interpreter::{DataType, Variable},
+    helper::{
+        comparable, extract_data, get_variable, joined, magic_variable, represent_datatype,
+        throw_warning, MAGIC_VARIABLES,
+    },
+    interpreter::{Context, DataType, Variable},
+    lexer::is_word_character,
+    variables::Variables,
 };
 
-pub fn silm_typeof(tokens: Vec<&str>, variables: &[Variable]) -> Result<Variable, String> {
+pub fn silm_typeof(
+    tokens: Vec<&str>,
+    variables: &Variables,
+    context: &mut Context,
+) -> Result<Variable, String> {
     if !tokens.is_empty() {
-        let argument_str = &mut tokens[0..].join(" ");
+        let text = context.text.clone();
+        let argument_str = &joined(&tokens[0..], &text);
 
         if argument_str.starts_with('(') && argument_str.ends_with(')') {
-            argument_str.remove(0);
-            argument_str.pop();
+            let argument_str = &argument_str[1..argument_str.len() - 1];
 
-            match extract_data(argument_str, variables) {
+            match extract_data(argument_str, variables, context) {
                 Ok(argument_option) => {
                     if let Some(argument) = argument_option {
                         Ok(Variable {
-                            identifier: "$returned$".to_string(),
+                            identifier: "$returned$".into(),
                             datatype: DataType::Str,
                             value: represent_datatype(argument.datatype).to_string(),
                         })
@@ -36,25 +45,36 @@ pub fn silm_typeof(tokens: Vec<&str>, variables: &[Variable]) -> Result<Variable
 
 #[test]
 fn test_silm_typeof() {
-    assert_eq!(silm_typeof(vec!["('A')"], &[]).unwrap().value, "char");
-    assert!(silm_typeof(vec!["(a)"], &[]).is_err());
+    let mut context = Context::default();
+
+    assert_eq!(
+        silm_typeof(vec!["('A')"], &Variables::new(), &mut context)
+            .unwrap()
+            .value,
+        "char"
+    );
+    assert!(silm_typeof(vec!["(a)"], &Variables::new(), &mut context).is_err());
 }
 
-pub fn silm_nameof(tokens: Vec<&str>, variables: &[Variable]) -> Result<Variable, String> {
+pub fn silm_nameof(
+    tokens: Vec<&str>,
+    variables: &Variables,
+    context: &mut Context,
+) -> Result<Variable, String> {
     if !tokens.is_empty() {
-        let argument_str = &mut tokens[0..].join(" ");
+        let text = context.text.clone();
+        let argument_str = &joined(&tokens[0..], &text);
 
         if argument_str.starts_with('(') && argument_str.ends_with(')') {
-            argument_str.remove(0);
-            argument_str.pop();
+            let argument_str = &argument_str[1..argument_str.len() - 1];
 
-            match extract_data(argument_str, variables) {
+            match extract_data(argument_str, variables, context) {
                 Ok(argument_option) => {
                     if let Some(argument) = argument_option {
                         Ok(Variable {
                             identifier: "$returned$".into(),
                             datatype: DataType::Str,
-                            value: argument.identifier,
+                            value: argument.identifier.to_string(),
                         })
                     } else {
                         Err("function requires one argument".to_string())
@@ -73,8 +93,12 @@ pub fn silm_nameof(tokens: Vec<&str>, variables: &[Variable]) -> Result<Variable
 
 #[test]
 fn test_silm_nameof() {
+    let mut context = Context::default();
+
     assert_eq!(
-        silm_nameof(vec!["(\"Test\")"], &[]).unwrap().value,
+        silm_nameof(vec!["(\"Test\")"], &Variables::new(), &mut context)
+            .unwrap()
+            .value,
         "$uninitialised$"
     );
     assert_eq!(
@@ -85,50 +109,67 @@ fn test_silm_nameof() {
                 datatype: DataType::Bool,
                 value: "false".into()
             }]
+            .into_iter()
+            .collect::<Variables>(),
+            &mut context
         )
         .unwrap()
         .value,
         "b"
     );
-    assert!(silm_nameof(vec!["(a)"], &[]).is_err());
+    assert!(silm_nameof(vec!["(a)"], &Variables::new(), &mut context).is_err());
 }
 
-pub fn silm_eq(tokens: Vec<&str>, variables: &[Variable]) -> Result<Variable, String> {
+pub fn silm_eq(
+    tokens: Vec<&str>,
+    variables: &Variables,
+    context: &mut Context,
+) -> Result<Variable, String> {
     if tokens.len() >= 2 {
-        let arguments = &mut tokens[0..].join(" ");
+        let text = context.text.clone();
+        let arguments = &joined(&tokens[0..], &text);
 
         if arguments.starts_with('(') && arguments.ends_with(')') {
-            arguments.remove(0);
-            arguments.pop();
+            let arguments = &arguments[1..arguments.len() - 1];
 
             match arguments.rsplit_once(',') {
                 Some((first_argument_str, second_argument_str)) => {
-                    match extract_data(first_argument_str, variables) {
+                    match extract_data(first_argument_str, variables, context) {
                         Ok(first_argument_option) => {
                             if let Some(first_argument) = first_argument_option {
-                                match extract_data(second_argument_str, variables) {
+                                match extract_data(second_argument_str, variables, context) {
                                     Ok(second_argument_option) => {
                                         if let Some(second_argument) = second_argument_option {
-                                            if first_argument.datatype == second_argument.datatype {
-                                                if first_argument.value == second_argument.value {
-                                                    Ok(Variable {
-                                                        datatype: DataType::Bool,
-                                                        identifier: "$returned$".into(),
-                                                        value: "true".into(),
-                                                    })
-                                                } else {
+                                            match equal(&first_argument, &second_argument) {
+                                                Some(equal) => Ok(Variable {
+                                                    datatype: DataType::Bool,
+                                                    identifier: "$returned$".into(),
+                                                    value: equal.to_string(),
+                                                }),
+
+                                                None => {
+                                                    context.error_at = context.span_of(arguments);
+
+                                                    throw_warning(
+                                                        &format!(
+                                                            "comparing {} with {} is always false",
+                                                            represent_datatype(
+                                                                first_argument.datatype
+                                                            ),
+                                                            represent_datatype(
+                                                                second_argument.datatype
+                                                            )
+                                                        ),
+                                                        "eq",
+                                                        context,
+                                                    );
+
                                                     Ok(Variable {
                                                         datatype: DataType::Bool,
                                                         identifier: "$returned$".into(),
                                                         value: "false".into(),
                                                     })
                                                 }
-                                            } else {
-                                                Ok(Variable {
-                                                    datatype: DataType::Bool,
-                                                    identifier: "$returned$".into(),
-                                                    value: "false".into(),
-                                                })
                                             }
                                         } else {
                                             Err("second argument not given".into())
@@ -158,55 +199,108 @@ pub fn silm_eq(tokens: Vec<&str>, variables: &[Variable]) -> Result<Variable, St
 
 #[test]
 fn test_silm_eq() {
-    assert_eq!(silm_eq(vec!["(1,", "1)"], &[]).unwrap().value, "true");
+    let io = crate::stdio::MemoryIo::new("");
+    let mut context = Context {
+        io: Box::new(io.clone()),
+        ..Default::default()
+    };
+
     assert_eq!(
-        silm_eq(vec!["(\"same", "phrase\",", "\"same", "phrase\")"], &[])
+        silm_eq(vec!["(1,", "1)"], &Variables::new(), &mut context)
             .unwrap()
             .value,
         "true"
     );
     assert_eq!(
-        silm_eq(vec!["(false,", "true)"], &[]).unwrap().value,
+        silm_eq(
+            vec!["(\"same", "phrase\",", "\"same", "phrase\")"],
+            &Variables::new(),
+            &mut context
+        )
+        .unwrap()
+        .value,
+        "true"
+    );
+    assert_eq!(
+        silm_eq(vec!["(false,", "true)"], &Variables::new(), &mut context)
+            .unwrap()
+            .value,
+        "false"
+    );
+
+    // Ints and floats are compared by their number, which arithmetic always gives
+    assert_eq!(
+        silm_eq(vec!["(1", "+", "2,", "3)"], &Variables::new(), &mut context)
+            .unwrap()
+            .value,
+        "true"
+    );
+    assert_eq!(context.errors, 0);
+
+    // Other different datatypes are never equal, which `-W` turns into an error
+    context.warnings = crate::interpreter::Warnings::Error;
+    assert_eq!(
+        silm_eq(vec!["(1,", "\"1\")"], &Variables::new(), &mut context)
+            .unwrap()
+            .value,
         "false"
     );
+    assert_eq!(context.errors, 1);
+    assert!(io
+        .stdout_text()
+        .starts_with("error at :0: eq: comparing int with str is always false"));
 }
 
-pub fn silm_ne(tokens: Vec<&str>, variables: &[Variable]) -> Result<Variable, String> {
+pub fn silm_ne(
+    tokens: Vec<&str>,
+    variables: &Variables,
+    context: &mut Context,
+) -> Result<Variable, String> {
     if tokens.len() >= 2 {
-        let arguments = &mut tokens[0..].join(" ");
+        let text = context.text.clone();
+        let arguments = &joined(&tokens[0..], &text);
 
         if arguments.starts_with('(') && arguments.ends_with(')') {
-            arguments.remove(0);
-            arguments.pop();
+            let arguments = &arguments[1..arguments.len() - 1];
 
             match arguments.rsplit_once(',') {
                 Some((first_argument_str, second_argument_str)) => {
-                    match extract_data(first_argument_str, variables) {
+                    match extract_data(first_argument_str, variables, context) {
                         Ok(first_argument_option) => {
                             if let Some(first_argument) = first_argument_option {
-                                match extract_data(second_argument_str, variables) {
+                                match extract_data(second_argument_str, variables, context) {
                                     Ok(second_argument_option) => {
                                         if let Some(second_argument) = second_argument_option {
-                                            if first_argument.datatype == second_argument.datatype {
-                                                if first_argument.value == second_argument.value {
-                                                    Ok(Variable {
-                                                        datatype: DataType::Bool,
-                                                        identifier: "$returned$".into(),
-                                                        value: "false".into(),
-                                                    })
-                                                } else {
+                                            match equal(&first_argument, &second_argument) {
+                                                Some(equal) => Ok(Variable {
+                                                    datatype: DataType::Bool,
+                                                    identifier: "$returned$".into(),
+                                                    value: (!equal).to_string(),
+                                                }),
+
+                                                None => {
+                                                    context.error_at = context.span_of(arguments);
+
+                                                    throw_warning(
+                                                        &format!(
+                                                            "comparing {} with {} is always true",
+                                                            represent_datatype(
+                                                                first_argument.datatype
+                                                            ),
+                                                            represent_datatype(
+                                                                second_argument.datatype
+                                                            )
+                                                        ),
+                                                        "ne",
+                                                        context,
+                                                    );
+
                                                     Ok(Variable {
                                                         datatype: DataType::Bool,
                                                         identifier: "$returned$".into(),
                                                         value: "true".into(),
                                                     })
                                                 }
-                                            } else {
-                                                Ok(Variable {
-                                                    datatype: DataType::Bool,
-                                                    identifier: "$returned$".into(),
-                                                    value: "true".into(),
-                                                })
                                             }
                                         } else {
                                             Err("second argument not given".into())
@@ -236,42 +330,98 @@ pub fn silm_ne(tokens: Vec<&str>, variables: &[Variable]) -> Result<Variable, St
 
 #[test]
 fn test_silm_ne() {
-    assert_eq!(silm_ne(vec!["(1,", "1)"], &[]).unwrap().value, "false");
+    let mut context = Context::default();
+
     assert_eq!(
-        silm_ne(vec!["(\"same", "phrase\",", "\"same", "phrase\")"], &[])
+        silm_ne(vec!["(1,", "1)"], &Variables::new(), &mut context)
             .unwrap()
             .value,
         "false"
     );
     assert_eq!(
-        silm_ne(vec!["(false,", "true)"], &[]).unwrap().value,
+        silm_ne(
+            vec!["(\"same", "phrase\",", "\"same", "phrase\")"],
+            &Variables::new(),
+            &mut context
+        )
+        .unwrap()
+        .value,
+        "false"
+    );
+    assert_eq!(
+        silm_ne(vec!["(false,", "true)"], &Variables::new(), &mut context)
+            .unwrap()
+            .value,
         "true"
     );
+    assert_eq!(
+        silm_ne(vec!["(2.0,", "2)"], &Variables::new(), &mut context)
+            .unwrap()
+            .value,
+        "false"
+    );
 }
 
-pub fn silm_format(tokens: Vec<&str>, variables: &[Variable]) -> Result<Variable, String> {
+// Whether two values are equal, `None` when their datatypes never are
+fn equal(first: &Variable, second: &Variable) -> Option<bool> {
+    if first.datatype == second.datatype {
+        Some(first.value == second.value)
+    } else if comparable(first.datatype, second.datatype) {
+        let number = |variable: &Variable| variable.value.parse::<f64>().ok();
+
+        Some(number(first).is_some() && number(first) == number(second))
+    } else {
+        None
+    }
+}
+
+pub fn silm_format(
+    tokens: Vec<&str>,
+    variables: &Variables,
+    context: &mut Context,
+) -> Result<Variable, String> {
     if !tokens.is_empty() {
-        let argument_str = &mut tokens[0..].join(" ");
+        let text = context.text.clone();
+        let argument_str = &joined(&tokens[0..], &text);
 
         if argument_str.starts_with('(') && argument_str.ends_with(')') {
-            argument_str.remove(0);
-            argument_str.pop();
+            let argument_str = &argument_str[1..argument_str.len() - 1];
 
-            match extract_data(argument_str, variables) {
+            match extract_data(argument_str, variables, context) {
                 Ok(argument_option) => {
                     if let Some(argument) = argument_option {
+                        for placeholder in unknown_placeholders(&argument.value, variables) {
+                            // Underlined in the string when it's written right there
+                            let written = format!("{{{}}}", placeholder);
+
+                            context.error_at = argument_str.find(&written).and_then(|start| {
+                                context.span_of(&argument_str[start..start + written.len()])
+                            });
+
+                            throw_warning(
+                                &format!(
+                                    "placeholder '{{{}}}' doesn't match any variable",
+                                    placeholder
+                                ),
+                                "format",
+                                context,
+                            );
+                        }
+
                         let mut result = argument.value;
 
-                        // Can't loop over `variables` twice or else the borrow checker will go bananas
-                        for index in 0..variables.len() {
-                            let variable = &variables[index];
+                        for variable in variables {
                             let placeholder = format!("{{{}}}", variable.identifier);
+                            result = result.replace(&placeholder, &variable.value);
+                        }
+
+                        for name in MAGIC_VARIABLES {
+                            let placeholder = format!("{{{}}}", name);
 
-                            if let Some(found_variable) =
-                                get_variable(&variable.identifier, variables)
-                            {
-                                let value = found_variable.value;
-                                result = result.replace(&placeholder, &value);
+                            if result.contains(&placeholder) {
+                                if let Some(variable) = magic_variable(name, context) {
+                                    result = result.replace(&placeholder, &variable.value);
+                                }
                             }
                         }
 
@@ -294,3 +444,29 @@ pub fn silm_format(tokens: Vec<&str>, variables: &[Variable]) -> Result<Variable
         Err("command requires one argument".into())
     }
 }
+
+// Names in `{name}` placeholders that don't belong to any variable, and so stay as they are
+fn unknown_placeholders<'a>(text: &'a str, variables: &Variables) -> Vec<&'a str> {
+    text.split('{')
+        .skip(1)
+        .filter_map(|piece| piece.split_once('}').map(|(name, _)| name))
+        .filter(|name| !name.is_empty() && name.chars().all(is_word_character))
+        .filter(|name| get_variable(name, variables).is_none() && !MAGIC_VARIABLES.contains(name))
+        .collect()
+}
+
+#[test]
+fn test_unknown_placeholders() {
+    let variables = [Variable {
+        identifier: "name".into(),
+        datatype: DataType::Str,
+        value: "silm".into(),
+    }]
+    .into_iter()
+    .collect::<Variables>();
+
+    assert_eq!(
+        unknown_placeholders("{name} says {greeting}, {} {not a name}", &variables),
+        vec!["greeting"]
+    );
+}
@@ -1,10 +1,34 @@
+use std::borrow::Cow;
+
 use crate::{
     functions::{silm_eq, silm_format, silm_nameof, silm_ne, silm_typeof},
-    interpreter::{DataType, Variable},
+    host,
+    interpreter::{Context, DataType, Frame, Variable, Warnings},
+    variables::Variables,
+    version::VERSION,
 };
 
+pub fn extract_data(
+    data: &str,
+    variables: &Variables,
+    context: &mut Context,
+) -> Result<Option<Variable>, String> {
+    let extracted = extract_value(data, variables, context);
+
+    // Whatever went wrong is somewhere in `data`, unless something in it knows better
+    if extracted.is_err() && context.error_at.is_none() {
+        context.error_at = context.span_of(data.trim());
+    }
+
+    extracted
+}
+
 // This function goes hard, feel free to copy & paste
-pub fn extract_data(data: &str, variables: &[Variable]) -> Result<Option<Variable>, String> {
+fn extract_value(
+    data: &str,
+    variables: &Variables,
+    context: &mut Context,
+) -> Result<Option<Variable>, String> {
     let datatype;
     let value;
 
@@ -20,7 +44,7 @@ pub fn extract_data(data: &str, variables: &[Variable]) -> Result<Option<Variabl
                 .to_string();
 
             Ok(Some(Variable {
-                identifier: "$uninitialised$".to_string(),
+                identifier: "$uninitialised$".into(),
                 datatype,
                 value,
             }))
@@ -34,7 +58,7 @@ pub fn extract_data(data: &str, variables: &[Variable]) -> Result<Option<Variabl
             if !value.is_empty() {
                 match value.parse::<char>() {
                     Ok(_) => Ok(Some(Variable {
-                        identifier: "$uninitialised$".to_string(),
+                        identifier: "$uninitialised$".into(),
                         datatype,
                         value,
                     })),
@@ -43,7 +67,7 @@ pub fn extract_data(data: &str, variables: &[Variable]) -> Result<Option<Variabl
                 }
             } else {
                 Ok(Some(Variable {
-                    identifier: "$uninitialised$".to_string(),
+                    identifier: "$uninitialised$".into(),
                     datatype,
                     value: "".to_string(),
                 }))
@@ -53,15 +77,15 @@ pub fn extract_data(data: &str, variables: &[Variable]) -> Result<Option<Variabl
             value = data.to_string();
 
             Ok(Some(Variable {
-                identifier: "$uninitialised$".to_string(),
+                identifier: "$uninitialised$".into(),
                 datatype,
                 value,
             }))
         } else if data.chars().next().unwrap().is_ascii_digit() || data.starts_with('-') {
             if data_tokens.len() > 1 {
-                match shunting_yard(data_tokens, variables) {
+                match shunting_yard(data_tokens, variables, context) {
                     Ok(result) => Ok(Some(Variable {
-                        identifier: "$uninitialised$".to_string(),
+                        identifier: "$uninitialised$".into(),
                         datatype: DataType::Float,
                         value: result.to_string(),
                     })),
@@ -72,7 +96,7 @@ pub fn extract_data(data: &str, variables: &[Variable]) -> Result<Option<Variabl
                 match data_tokens[0].parse::<f64>() {
                     Ok(value) => Ok(Some(Variable {
                         datatype: DataType::Float,
-                        identifier: "$uninitialised$".to_string(),
+                        identifier: "$uninitialised$".into(),
                         value: value.to_string(),
                     })),
 
@@ -82,7 +106,7 @@ pub fn extract_data(data: &str, variables: &[Variable]) -> Result<Option<Variabl
                 match data_tokens[0].parse::<i32>() {
                     Ok(value) => Ok(Some(Variable {
                         datatype: DataType::Int,
-                        identifier: "$uninitialised$".to_string(),
+                        identifier: "$uninitialised$".into(),
                         value: value.to_string(),
                     })),
 
@@ -90,15 +114,20 @@ pub fn extract_data(data: &str, variables: &[Variable]) -> Result<Option<Variabl
                 }
             }
         } else if data_tokens.len() == 1 {
-            match get_variable(data, variables) {
-                Some(variable) => Ok(Some(variable)),
+            match lookup_variable(data, variables, context) {
+                Some(variable) => Ok(Some(variable.into_owned())),
 
-                None => Err(format!("variable '{}' unrecognised", data)),
+                None => Err(format!(
+                    "variable '{}' unrecognised{}",
+                    data,
+                    did_you_mean(data, variables)
+                )),
             }
         } else if let Some(result) = call_function(
             data_tokens[0],
             data_tokens[1..].to_vec(),
-            &mut variables.to_vec(),
+            variables,
+            context,
         ) {
             match result {
                 Ok(returned_value) => Ok(Some(returned_value)),
@@ -106,9 +135,9 @@ pub fn extract_data(data: &str, variables: &[Variable]) -> Result<Option<Variabl
                 Err(error) => Err(error),
             }
         } else {
-            match shunting_yard(data_tokens, variables) {
+            match shunting_yard(data_tokens, variables, context) {
                 Ok(result) => Ok(Some(Variable {
-                    identifier: "$uninitialised$".to_string(),
+                    identifier: "$uninitialised$".into(),
                     datatype: DataType::Float,
                     value: result.to_string(),
                 })),
@@ -123,100 +152,159 @@ pub fn extract_data(data: &str, variables: &[Variable]) -> Result<Option<Variabl
 
 #[test]
 fn test_extract_data() {
-    let variables: Vec<Variable> = vec![Variable {
-        identifier: "x".to_string(),
+    let variables = [Variable {
+        identifier: "x".into(),
         datatype: DataType::Int,
         value: "10".to_string(),
-    }];
+    }]
+    .into_iter()
+    .collect::<Variables>();
+    let mut context = Context::default();
 
     assert_eq!(
-        extract_data("10", &variables),
+        extract_data("10", &variables, &mut context),
         Ok(Some(Variable {
-            identifier: "$uninitialised$".to_string(),
+            identifier: "$uninitialised$".into(),
             datatype: DataType::Int,
             value: "10".to_string()
         }))
     );
 
     assert_eq!(
-        extract_data("1.0", &variables),
+        extract_data("1.0", &variables, &mut context),
         Ok(Some(Variable {
-            identifier: "$uninitialised$".to_string(),
+            identifier: "$uninitialised$".into(),
             datatype: DataType::Float,
             value: "1".to_string()
         }))
     );
 
     assert_eq!(
-        extract_data("\"alhamdulillah\"", &variables),
+        extract_data("\"alhamdulillah\"", &variables, &mut context),
         Ok(Some(Variable {
-            identifier: "$uninitialised$".to_string(),
+            identifier: "$uninitialised$".into(),
             datatype: DataType::Str,
             value: "alhamdulillah".to_string()
         }))
     );
 
     assert_eq!(
-        extract_data("'W'", &variables),
+        extract_data("'W'", &variables, &mut context),
         Ok(Some(Variable {
-            identifier: "$uninitialised$".to_string(),
+            identifier: "$uninitialised$".into(),
             datatype: DataType::Char,
             value: "W".to_string()
         }))
     );
 
     assert_eq!(
-        extract_data("true", &variables),
+        extract_data("true", &variables, &mut context),
         Ok(Some(Variable {
-            identifier: "$uninitialised$".to_string(),
+            identifier: "$uninitialised$".into(),
             datatype: DataType::Bool,
             value: "true".to_string()
         }))
     );
 
     assert_eq!(
-        extract_data("10 + x", &variables),
+        extract_data("10 + x", &variables, &mut context),
         Ok(Some(Variable {
-            identifier: "$uninitialised$".to_string(),
+            identifier: "$uninitialised$".into(),
             datatype: DataType::Float,
             value: "20".to_string()
         }))
     );
 }
 
-pub fn assign(variable: Variable, variables: &mut Vec<Variable>) {
-    match variables
-        .iter_mut()
-        .find(|var| var.identifier == variable.identifier)
-    {
-        Some(existing_variable) => {
-            existing_variable.value = variable.value;
-            existing_variable.datatype = variable.datatype;
-        }
+// Gives the variable the datatype it was declared with, ints being fine where floats are
+// and whole floats where ints are, since arithmetic always gives a float
+pub fn coerce(variable: Variable, datatype: DataType) -> Result<Variable, String> {
+    let whole = match (variable.datatype, datatype) {
+        (DataType::Float, DataType::Int) => variable
+            .value
+            .parse::<f64>()
+            .ok()
+            .filter(|number| number.fract() == 0.0)
+            .filter(|number| *number >= i32::MIN as f64 && *number <= i32::MAX as f64),
+        _ => None,
+    };
 
-        None => {
-            variables.push(variable);
+    if variable.datatype == datatype {
+        Ok(variable)
+    } else if variable.datatype == DataType::Int && datatype == DataType::Float {
+        Ok(Variable {
+            datatype,
+            ..variable
+        })
+    } else if let Some(number) = whole {
+        Ok(Variable {
+            datatype,
+            value: (number as i32).to_string(),
+            ..variable
+        })
+    } else {
+        Err(mismatched_types(
+            &variable.identifier,
+            datatype,
+            variable.datatype,
+        ))
+    }
+}
+
+// Assigns unless the name is taken by a constant or is a built-in `$name$`, and holds
+// the value to the datatype the name was declared with
+pub fn assign(variable: Variable, variables: &mut Variables) -> Result<(), String> {
+    let variable = check_assignable(variable, variables)?;
+    variables.assign(variable);
+
+    Ok(())
+}
+
+pub fn assign_constant(variable: Variable, variables: &mut Variables) -> Result<(), String> {
+    let variable = check_assignable(variable, variables)?;
+    variables.assign_constant(variable);
+
+    Ok(())
+}
+
+fn check_assignable(variable: Variable, variables: &Variables) -> Result<Variable, String> {
+    let identifier = &variable.identifier;
+
+    if identifier.len() > 1 && identifier.starts_with('$') && identifier.ends_with('$') {
+        Err(format!(
+            "'{}' is built in and can't be assigned",
+            identifier
+        ))
+    } else if variables.is_constant(identifier) {
+        Err(format!(
+            "'{}' is a constant and can't be reassigned",
+            identifier
+        ))
+    } else {
+        match variables.annotation(identifier) {
+            Some(datatype) => coerce(variable, datatype),
+            None => Ok(variable),
         }
     }
 }
 
 #[test]
 fn test_assign() {
-    let mut variables: Vec<Variable> = Vec::new();
+    let mut variables: Variables = Variables::new();
 
-    variables.push(Variable {
-        identifier: "already_there".to_string(),
+    variables.assign(Variable {
+        identifier: "already_there".into(),
         datatype: DataType::Bool,
         value: "false".to_string(),
     });
 
     let new_variable = Variable {
-        identifier: "new_var".to_string(),
+        identifier: "new_var".into(),
         datatype: DataType::Int,
         value: "10".to_string(),
     };
 
-    assign(new_variable, &mut variables);
+    assert_eq!(assign(new_variable, &mut variables), Ok(()));
 
     assert!(variables
         .iter()
@@ -226,18 +314,66 @@ fn test_assign() {
 
     assign(
         Variable {
-            identifier: "already_there".to_string(),
+            identifier: "already_there".into(),
             datatype: DataType::Str,
             value: "changed now!".to_string(),
         },
         &mut variables,
-    );
+    )
+    .unwrap();
 
     assert!(variables
         .iter()
         .any(|variable| variable.identifier == *"already_there"
             && variable.datatype == DataType::Str
             && variable.value == *"changed now!"));
+
+    let constant = Variable {
+        identifier: "pi".into(),
+        datatype: DataType::Float,
+        value: "3.14".to_string(),
+    };
+
+    assert_eq!(assign_constant(constant.clone(), &mut variables), Ok(()));
+    assert_eq!(
+        assign(constant.clone(), &mut variables),
+        Err("'pi' is a constant and can't be reassigned".to_string())
+    );
+    assert!(assign_constant(constant, &mut variables).is_err());
+
+    assert_eq!(
+        assign(
+            Variable {
+                identifier: "$version$".into(),
+                datatype: DataType::Str,
+                value: "1.0".to_string(),
+            },
+            &mut variables,
+        ),
+        Err("'$version$' is built in and can't be assigned".to_string())
+    );
+
+    variables.annotate("count", DataType::Float);
+
+    let count = |datatype: DataType, value: &str| Variable {
+        identifier: "count".into(),
+        datatype,
+        value: value.to_string(),
+    };
+
+    assert_eq!(assign(count(DataType::Int, "1"), &mut variables), Ok(()));
+    assert_eq!(variables.get("count").unwrap().datatype, DataType::Float);
+    assert_eq!(
+        assign(count(DataType::Str, "one"), &mut variables),
+        Err("mismatched types: 'count' is declared as float, got str".to_string())
+    );
+
+    assert_eq!(
+        coerce(count(DataType::Float, "3"), DataType::Int),
+        Ok(count(DataType::Int, "3"))
+    );
+    assert!(coerce(count(DataType::Float, "2.5"), DataType::Int).is_err());
+    assert!(coerce(count(DataType::Float, "1e30"), DataType::Int).is_err());
 }
 
 pub fn represent_datatype(datatype: DataType) -> &'static str {
@@ -256,43 +392,476 @@ pub fn represent_datatype(datatype: DataType) -> &'static str {
     }
 }
 
-pub fn get_variable(identifier: &str, variables: &[Variable]) -> Option<Variable> {
-    variables
-        .iter()
-        .find(|var| var.identifier == identifier)
-        .map(|found_variable| Variable {
-            datatype: found_variable.datatype,
-            identifier: found_variable.identifier.clone(),
-            value: found_variable.value.clone(),
-        })
+// A value the way the interactive mode and the debugger show it, with its type
+pub fn represent_value(variable: &Variable) -> String {
+    match variable.datatype {
+        DataType::Str => format!("\"{}\" (str)", variable.value),
+        DataType::Char => format!("'{}' (char)", variable.value),
+        DataType::Block => "block".to_string(),
+        _ => format!(
+            "{} ({})",
+            variable.value,
+            represent_datatype(variable.datatype)
+        ),
+    }
+}
+
+// What `silm check` and a run both say about types and annotations, so they match
+pub fn unknown_type(name: &str) -> String {
+    format!(
+        "unknown type `{}`, expected bool, int, float, str, char or block",
+        name
+    )
+}
+
+pub fn mismatched_types(name: &str, expected: DataType, got: DataType) -> String {
+    format!(
+        "mismatched types: '{}' is declared as {}, got {}",
+        name,
+        represent_datatype(expected),
+        represent_datatype(got)
+    )
+}
+
+pub const UNSPACED_TYPE: &str = "expected a space between `:` and the type";
+pub const SPACED_COLON: &str = "expected the `:` right after the name";
+
+// Whether values of these datatypes can ever be equal, ints and floats being compared
+// by their number since arithmetic always gives a float
+pub fn comparable(first: DataType, second: DataType) -> bool {
+    let number = |datatype| datatype == DataType::Int || datatype == DataType::Float;
+
+    first == second || (number(first) && number(second))
+}
+
+pub fn parse_datatype(name: &str) -> Option<DataType> {
+    match name {
+        "bool" => Some(DataType::Bool),
+
+        "float" => Some(DataType::Float),
+
+        "int" => Some(DataType::Int),
+
+        "str" => Some(DataType::Str),
+
+        "char" => Some(DataType::Char),
+
+        "block" => Some(DataType::Block),
+
+        _ => None,
+    }
+}
+
+pub const MAGIC_VARIABLES: &[&str] = &["$version$", "$line$", "$input_name$", "$line_number$"];
+
+// `$version$`, `$line$`, `$input_name$` and `$line_number$`, worked out from the context
+// when they're used instead of being assigned before every line
+pub fn magic_variable(identifier: &str, context: &Context) -> Option<Variable> {
+    let (datatype, value) = match identifier {
+        "$version$" => (DataType::Str, VERSION.to_string()),
+        "$line$" => (DataType::Str, context.line.clone()),
+        "$input_name$" => (DataType::Str, context.input_name.clone()),
+        "$line_number$" => (DataType::Int, context.line_number.to_string()),
+        _ => return None,
+    };
+
+    Some(Variable {
+        datatype,
+        identifier: identifier.into(),
+        value,
+    })
+}
+
+// A variable of the scope, or a magic one, without copying the former
+pub fn lookup_variable<'a>(
+    identifier: &str,
+    variables: &'a Variables,
+    context: &Context,
+) -> Option<Cow<'a, Variable>> {
+    match get_variable(identifier, variables) {
+        Some(variable) => Some(Cow::Borrowed(variable)),
+        None => magic_variable(identifier, context).map(Cow::Owned),
+    }
+}
+
+pub fn get_variable<'a>(identifier: &str, variables: &'a Variables) -> Option<&'a Variable> {
+    variables.get(identifier)
 }
 
 #[test]
 fn test_get_variable() {
-    let mut variables: Vec<Variable> = Vec::new();
+    let mut variables: Variables = Variables::new();
 
     let new_variable = Variable {
-        identifier: "name".to_string(),
+        identifier: "name".into(),
         datatype: DataType::Str,
         value: "hasan".to_string(),
     };
 
-    variables.push(new_variable.clone());
+    variables.assign(new_variable.clone());
 
-    assert_eq!(get_variable("name", &variables), Some(new_variable));
+    assert_eq!(get_variable("name", &variables), Some(&new_variable));
     assert_eq!(get_variable("$uninitialised$", &variables), None);
 }
 
-pub fn throw_error(message: &str, current_function: &str, input_name: String, line_number: i32) {
-    println!(
-        "error at {}:{}: {}: {}",
-        input_name, line_number, current_function, message
+// Where `part` starts in `whole`, when it's a slice of it rather than a copy
+pub fn offset_in(whole: &str, part: &str) -> Option<usize> {
+    let offset = (part.as_ptr() as usize).checked_sub(whole.as_ptr() as usize)?;
+
+    (offset + part.len() <= whole.len()).then_some(offset)
+}
+
+#[test]
+fn test_offset_in() {
+    let line = "let x = x";
+
+    assert_eq!(offset_in(line, &line[8..]), Some(8));
+    assert_eq!(offset_in(&line[4..], &line[8..]), Some(4));
+    assert_eq!(offset_in(&line[4..], &line[..3]), None);
+    assert_eq!(offset_in(line, &String::from("x")), None);
+}
+
+// `tokens` joined by spaces, borrowed from `text` when that's how they're written in
+// it, so errors about what's inside can still point at the line
+pub fn joined<'a>(tokens: &[&str], text: &'a str) -> Cow<'a, str> {
+    let span = match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => offset_in(text, first)
+            .zip(offset_in(text, last))
+            .map(|(start, end)| (start, end + last.len())),
+        _ => None,
+    };
+
+    match span {
+        Some((start, end))
+            if start <= end && text[start..end].split(' ').eq(tokens.iter().copied()) =>
+        {
+            Cow::Borrowed(&text[start..end])
+        }
+        _ => Cow::Owned(tokens.join(" ")),
+    }
+}
+
+#[test]
+fn test_joined() {
+    let text = "println (eq (q,  q))";
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+
+    assert!(matches!(
+        joined(&tokens[..2], text),
+        Cow::Borrowed("println (eq")
+    ));
+
+    // Spaced differently than a join would, so it has to be a copy
+    assert!(matches!(joined(&tokens[1..], text), Cow::Owned(joined) if joined == "(eq (q, q))"));
+    assert!(matches!(joined(&["(1,", "2)"], text), Cow::Owned(joined) if joined == "(1, 2)"));
+}
+
+// Splits on commas that aren't nested inside parantheses, strings or chars,
+// unlike `rsplit_once(',')` which breaks on things like `eq (x, 1), "message"`
+pub fn split_arguments(arguments: &str) -> Vec<&str> {
+    let mut result: Vec<&str> = Vec::new();
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+
+    for (index, character) in arguments.char_indices() {
+        match quote {
+            Some(opening) => {
+                if character == opening {
+                    quote = None;
+                }
+            }
+
+            None => match character {
+                '"' | '\'' => quote = Some(character),
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                ',' if depth == 0 => {
+                    result.push(arguments[start..index].trim());
+                    start = index + 1;
+                }
+                _ => {}
+            },
+        }
+    }
+
+    if !arguments[start..].trim().is_empty() || !result.is_empty() {
+        result.push(arguments[start..].trim());
+    }
+
+    result
+}
+
+#[test]
+fn test_split_arguments() {
+    assert_eq!(
+        split_arguments("eq (x, 1), \"a, b\""),
+        vec!["eq (x, 1)", "\"a, b\""]
+    );
+    assert_eq!(split_arguments("','"), vec!["','"]);
+    assert_eq!(split_arguments("x,"), vec!["x", ""]);
+    assert!(split_arguments(" ").is_empty());
+}
+
+// The values of comma separated arguments, for host functions and blocks
+pub fn extract_arguments(
+    arguments: &str,
+    variables: &Variables,
+    context: &mut Context,
+) -> Result<Vec<Variable>, String> {
+    let mut values: Vec<Variable> = Vec::new();
+
+    for (index, argument) in split_arguments(arguments).into_iter().enumerate() {
+        match extract_data(argument, variables, context)? {
+            Some(value) => values.push(value),
+            None => return Err(format!("argument {} is empty", index + 1)),
+        }
+    }
+
+    Ok(values)
+}
+
+pub fn check_arity(arity: usize, given: usize) -> Result<(), String> {
+    if given == arity {
+        Ok(())
+    } else {
+        Err(format!(
+            "expected {} argument{}, got {}",
+            arity,
+            if arity == 1 { "" } else { "s" },
+            given
+        ))
+    }
+}
+
+pub fn throw_error(
+    message: &str,
+    current_function: &str,
+    input_name: String,
+    line_number: i32,
+    context: &mut Context,
+) {
+    context.errors += 1;
+
+    let report = report(
+        "error",
+        message,
+        current_function,
+        &input_name,
+        line_number,
+        context,
+    );
+
+    // Errors are part of a script's output, so they go where `println` does
+    let _ = write!(context.io.stdout(), "{}", report);
+}
+
+// Something that's probably a mistake but doesn't stop the line, printed to stderr
+// at the line being run, unless `--quiet-warnings` hides it or `-W` makes it an error
+pub fn throw_warning(message: &str, current_function: &str, context: &mut Context) {
+    let input_name = context.input_name.clone();
+    let line_number = context.line_number;
+
+    match context.warnings {
+        Warnings::Show => {
+            let report = report(
+                "warning",
+                message,
+                current_function,
+                &input_name,
+                line_number,
+                context,
+            );
+
+            let _ = write!(context.io.stderr(), "{}", report);
+        }
+
+        Warnings::Quiet => {}
+
+        Warnings::Error => throw_error(message, current_function, input_name, line_number, context),
+    }
+}
+
+fn report(
+    kind: &str,
+    message: &str,
+    current_function: &str,
+    input_name: &str,
+    line_number: i32,
+    context: &mut Context,
+) -> String {
+    let mut report = match error_span(context) {
+        Some((column, end)) => format!(
+            "{} at {}:{}:{}: {}: {}\n{}",
+            kind,
+            input_name,
+            line_number,
+            column + 1,
+            current_function,
+            message,
+            snippet(&context.line, line_number, column, end)
+        ),
+
+        None => format!(
+            "{} at {}:{}: {}: {}\n",
+            kind, input_name, line_number, current_function, message
+        ),
+    };
+
+    report.push_str(&traceback(&context.frames));
+    report
+}
+
+// Where on the line an error is, as found by whatever threw it or else the command
+// that was running
+fn error_span(context: &mut Context) -> Option<(usize, usize)> {
+    let text = context.text.clone();
+
+    context.error_at.take().or_else(|| {
+        let command = text.split_whitespace().next()?;
+
+        context.span_of(command)
+    })
+}
+
+// The line with the part from `column` to `end` underlined, like
+//   |
+// 3 | let y = x ^ 2
+//   |           ^
+pub fn snippet(line: &str, line_number: i32, column: usize, end: usize) -> String {
+    let gutter = " ".repeat(line_number.to_string().len());
+    let width = |text: &str| text.chars().count();
+
+    let column = column.min(line.len());
+    let end = end.clamp(column, line.len());
+
+    format!(
+        "{} |\n{} | {}\n{} | {}{}\n",
+        gutter,
+        line_number,
+        line,
+        gutter,
+        " ".repeat(width(&line[..column])),
+        "^".repeat(width(&line[column..end]).max(1))
+    )
+}
+
+#[test]
+fn test_snippet() {
+    assert_eq!(
+        snippet("let y = x ^ 2", 3, 10, 11),
+        "  |\n3 | let y = x ^ 2\n  |           ^\n"
+    );
+    assert_eq!(
+        snippet("println (nme)", 12, 9, 12),
+        "   |\n12 | println (nme)\n   |          ^^^\n"
+    );
+}
+
+// `, did you mean `name`?` when one of the variables is spelled almost like `name`
+pub fn did_you_mean(name: &str, variables: &Variables) -> String {
+    match suggest(
+        name,
+        variables
+            .iter()
+            .map(|variable| variable.identifier.as_str())
+            .filter(|identifier| !identifier.starts_with('$')),
+    ) {
+        Some(suggestion) => format!(", did you mean `{}`?", suggestion),
+        None => String::new(),
+    }
+}
+
+// The closest candidate that's at most a third of `name` away from it, so names
+// shorter than three characters never get a suggestion
+pub fn suggest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = name.chars().count() / 3;
+
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+#[test]
+fn test_suggest() {
+    let names = ["message", "name", "x"];
+
+    assert_eq!(suggest("mesage", names), Some("message"));
+    assert_eq!(suggest("nme", names), Some("name"));
+    assert_eq!(suggest("y", names), None);
+    assert_eq!(suggest("completely_different", names), None);
+}
+
+// Levenshtein distance, the number of characters to insert, remove or replace
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_character) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+
+        for (j, b_character) in b.iter().enumerate() {
+            let replace = previous[j] + usize::from(a_character != *b_character);
+            current.push(replace.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+// Every file, block and `eval` that led to an error, innermost first. Nothing for
+// errors at the top of a file, where the error's own location says it all
+pub fn traceback(frames: &[Frame]) -> String {
+    if frames.len() < 2 {
+        return String::new();
+    }
+
+    frames
+        .iter()
+        .rev()
+        .map(|frame| {
+            format!(
+                "  at {}:{} ({})\n",
+                frame.input_name, frame.line_number, frame.kind
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_traceback() {
+    use crate::interpreter::FrameKind;
+
+    let frame = |kind, input_name: &str, line_number| Frame {
+        kind,
+        input_name: input_name.to_string(),
+        line_number,
+    };
+
+    assert_eq!(
+        traceback(&[
+            frame(FrameKind::File, "main.slm", 6),
+            frame(FrameKind::Interpret, "modules/greet.slm", 2),
+            frame(FrameKind::Block, "<block greet>", 1),
+        ]),
+        "  at <block greet>:1 (block call)\n  at modules/greet.slm:2 (interpret)\n  at main.slm:6 (file)\n"
     );
+    assert_eq!(traceback(&[frame(FrameKind::File, "main.slm", 6)]), "");
 }
 
 // I'm planning to make this function also return a DataType
 // an Int by default, or a Float if the result is so
-pub fn shunting_yard(tokens: Vec<&str>, variables: &[Variable]) -> Result<f64, String> {
+pub fn shunting_yard(
+    tokens: Vec<&str>,
+    variables: &Variables,
+    context: &mut Context,
+) -> Result<f64, String> {
     let mut output_queue: Vec<String> = Vec::new();
     let mut output_stack: Vec<f64> = Vec::new();
     let mut operator_stack: Vec<String> = Vec::new();
@@ -346,34 +915,35 @@ pub fn shunting_yard(tokens: Vec<&str>, variables: &[Variable]) -> Result<f64, S
                     output_stack.push(result);
                 }
             }
-        } else if variables
-            .iter()
-            .any(|variable| variable.identifier == token)
-        {
-            match get_variable(token, variables) {
-                Some(variable) => {
-                    if variable.datatype == DataType::Int || variable.datatype == DataType::Float {
-                        if let Ok(number) = variable.value.parse::<f64>() {
-                            output_queue.push(number.to_string());
-                            output_stack.push(number);
-                        } else {
-                            return Err(format!(
-                                "variable '{}' can not be parsed into a float",
-                                token
-                            ));
-                        }
-                    } else {
-                        return Err(format!(
-                            "variable '{}' must be either an int or a float",
-                            token
-                        ));
-                    }
+        } else if let Some(variable) = lookup_variable(token, variables, context) {
+            if variable.datatype == DataType::Int || variable.datatype == DataType::Float {
+                if let Ok(number) = variable.value.parse::<f64>() {
+                    output_queue.push(number.to_string());
+                    output_stack.push(number);
+                } else {
+                    context.error_at = context.span_of(token);
+
+                    return Err(format!(
+                        "variable '{}' can not be parsed into a float",
+                        token
+                    ));
                 }
+            } else {
+                context.error_at = context.span_of(token);
 
-                None => return Err(format!("invalid variable: {}", token)),
+                return Err(format!(
+                    "variable '{}' must be either an int or a float",
+                    token
+                ));
             }
         } else {
-            return Err(format!("invalid token: {}", token));
+            context.error_at = context.span_of(token);
+
+            return Err(format!(
+                "invalid token: {}{}",
+                token,
+                did_you_mean(token, variables)
+            ));
         }
     }
 
@@ -405,53 +975,73 @@ pub fn shunting_yard(tokens: Vec<&str>, variables: &[Variable]) -> Result<f64, S
 
 #[test]
 fn test_shunting_yard() {
-    let variables: Vec<Variable> = vec![Variable {
-        identifier: "x".to_string(),
+    let variables = [Variable {
+        identifier: "x".into(),
         datatype: DataType::Int,
         value: "256".to_string(),
-    }];
+    }]
+    .into_iter()
+    .collect::<Variables>();
 
     let tokens: Vec<&str> = "x + 1 + 2 - ( 3 * 4 ) / 5 % 7".split_whitespace().collect();
 
-    assert_eq!(shunting_yard(tokens, &variables), Ok(256.6));
+    assert_eq!(
+        shunting_yard(tokens, &variables, &mut Context::default()),
+        Ok(256.6)
+    );
 }
 
+// Names of the standard library functions `call_function` knows about
+pub const FUNCTIONS: &[&str] = &["typeof", "nameof", "eq", "ne", "format"];
+
 fn call_function(
     name: &str,
     tokens: Vec<&str>,
-    variables: &mut [Variable],
+    variables: &Variables,
+    context: &mut Context,
 ) -> Option<Result<Variable, String>> {
     match name {
-        "typeof" => match silm_typeof(tokens, variables) {
+        "typeof" => match silm_typeof(tokens, variables, context) {
             Ok(returned_value) => Some(Ok(returned_value)),
 
             Err(error) => Some(Err(format!("{}: {}", name, error))),
         },
 
-        "nameof" => match silm_nameof(tokens, variables) {
+        "nameof" => match silm_nameof(tokens, variables, context) {
             Ok(returned_value) => Some(Ok(returned_value)),
 
             Err(error) => Some(Err(format!("{}: {}", name, error))),
         },
 
-        "eq" => match silm_eq(tokens, variables) {
+        "eq" => match silm_eq(tokens, variables, context) {
             Ok(returned_value) => Some(Ok(returned_value)),
 
             Err(error) => Some(Err(format!("{}: {}", name, error))),
         },
 
-        "ne" => match silm_ne(tokens, variables) {
+        "ne" => match silm_ne(tokens, variables, context) {
             Ok(returned_value) => Some(Ok(returned_value)),
 
             Err(error) => Some(Err(format!("{}: {}", name, error))),
         },
 
-        "format" => match silm_format(tokens, variables) {
+        "format" => match silm_format(tokens, variables, context) {
             Ok(returned_value) => Some(Ok(returned_value)),
 
             Err(error) => Some(Err(format!("{}: {}", name, error))),
         },
 
-        _ => None,
+        // Functions registered by whoever embeds silm
+        _ => {
+            let function = context.host.get_function(name)?;
+
+            Some(
+                context
+                    .sandbox
+                    .check_name(name, &context.host)
+                    .and_then(|_| host::run(function, tokens, variables, context))
+                    .map_err(|error| format!("{}: {}", name, error)),
+            )
+        }
     }
 }
@@ -0,0 +1,313 @@
+use std::{collections::HashMap, fmt, rc::Rc};
+
+use crate::{
+    helper::{check_arity, extract_arguments, represent_datatype},
+    interpreter::{Context, DataType, Variable},
+    sandbox::Capability,
+    variables::Variables,
+};
+
+type Callback = Rc<dyn Fn(&[Variable]) -> Result<Variable, String>>;
+
+// Functions and commands written in Rust by whoever embeds silm, looked up after
+// the built-in ones
+#[derive(Default)]
+pub struct Host {
+    functions: HashMap<String, (usize, Callback)>,
+    commands: HashMap<String, (usize, Callback)>,
+    // What a function or command needs the sandbox to allow
+    capabilities: HashMap<String, Capability>,
+}
+
+impl fmt::Debug for Host {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("Host")
+            .field("functions", &self.functions.keys().collect::<Vec<_>>())
+            .field("commands", &self.commands.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Host {
+    // A function used inside commands like `let y = double (x)`, with its arguments
+    // converted from silm values and its result converted back
+    pub fn function<A, R, F>(&mut self, name: &str, function: F)
+    where
+        A: Arguments,
+        R: IntoVariable,
+        F: Fn(A) -> Result<R, String> + 'static,
+    {
+        self.raw_function(name, A::ARITY, move |arguments| {
+            function(A::from_variables(arguments)?).map(IntoVariable::into_variable)
+        });
+    }
+
+    // Like `function`, but with the arguments as they are, whatever their datatypes
+    pub fn raw_function(
+        &mut self,
+        name: &str,
+        arity: usize,
+        function: impl Fn(&[Variable]) -> Result<Variable, String> + 'static,
+    ) {
+        self.functions
+            .insert(name.to_string(), (arity, Rc::new(function)));
+    }
+
+    // A command that's used on a line of its own, like `notify ("done", 3)`
+    pub fn command<A, F>(&mut self, name: &str, command: F)
+    where
+        A: Arguments,
+        F: Fn(A) -> Result<(), String> + 'static,
+    {
+        self.commands.insert(
+            name.to_string(),
+            (
+                A::ARITY,
+                Rc::new(move |arguments: &[Variable]| {
+                    command(A::from_variables(arguments)?).map(|_| ().into_variable())
+                }),
+            ),
+        );
+    }
+
+    // Marks a function or command as needing something the sandbox can deny, like
+    // `Capability::Env` for one that reads environment variables
+    pub fn require(&mut self, name: &str, capability: Capability) {
+        self.capabilities.insert(name.to_string(), capability);
+    }
+
+    pub fn required(&self, name: &str) -> Option<Capability> {
+        self.capabilities.get(name).copied()
+    }
+
+    pub fn has_function(&self, name: &str) -> bool {
+        self.functions.contains_key(name)
+    }
+
+    pub fn has_command(&self, name: &str) -> bool {
+        self.commands.contains_key(name)
+    }
+
+    // Names of every registered function and command, for completion and the like
+    pub fn names(&self) -> Vec<&str> {
+        self.functions
+            .keys()
+            .chain(self.commands.keys())
+            .map(|name| name.as_str())
+            .collect()
+    }
+
+    // Handed out rather than called here, since evaluating the arguments needs the
+    // context the host lives in
+    pub fn get_function(&self, name: &str) -> Option<(usize, Callback)> {
+        self.functions
+            .get(name)
+            .map(|(arity, function)| (*arity, function.clone()))
+    }
+
+    pub fn get_command(&self, name: &str) -> Option<(usize, Callback)> {
+        self.commands
+            .get(name)
+            .map(|(arity, command)| (*arity, command.clone()))
+    }
+}
+
+// Evaluates the arguments in `(a, b)` and calls a registered function or command with them
+pub fn run(
+    (arity, callback): (usize, Callback),
+    tokens: Vec<&str>,
+    variables: &Variables,
+    context: &mut Context,
+) -> Result<Variable, String> {
+    let arguments = tokens.join(" ");
+
+    let arguments = match arguments
+        .strip_prefix('(')
+        .and_then(|arguments| arguments.strip_suffix(')'))
+    {
+        Some(arguments) => arguments,
+        None => return Err("arguments must be inside parantheses".to_string()),
+    };
+
+    let values = extract_arguments(arguments, variables, context)?;
+    check_arity(arity, values.len())?;
+
+    callback(&values)
+}
+
+pub trait FromVariable: Sized {
+    fn from_variable(variable: &Variable) -> Result<Self, DataType>;
+}
+
+pub trait IntoVariable {
+    fn into_variable(self) -> Variable;
+}
+
+fn returned(datatype: DataType, value: String) -> Variable {
+    Variable {
+        datatype,
+        identifier: "$returned$".into(),
+        value,
+    }
+}
+
+// Implements the conversions for a Rust type that's stored as `datatype`
+macro_rules! convert {
+    ($type:ty, $datatype:expr) => {
+        impl FromVariable for $type {
+            fn from_variable(variable: &Variable) -> Result<Self, DataType> {
+                if variable.datatype == $datatype {
+                    variable.value.parse().map_err(|_| $datatype)
+                } else {
+                    Err($datatype)
+                }
+            }
+        }
+
+        impl IntoVariable for $type {
+            fn into_variable(self) -> Variable {
+                returned($datatype, self.to_string())
+            }
+        }
+    };
+}
+
+convert!(i32, DataType::Int);
+convert!(String, DataType::Str);
+convert!(char, DataType::Char);
+convert!(bool, DataType::Bool);
+
+// Ints are accepted where floats are expected, like in arithmetic
+impl FromVariable for f64 {
+    fn from_variable(variable: &Variable) -> Result<Self, DataType> {
+        match variable.datatype {
+            DataType::Int | DataType::Float => variable.value.parse().map_err(|_| DataType::Float),
+
+            _ => Err(DataType::Float),
+        }
+    }
+}
+
+impl IntoVariable for f64 {
+    fn into_variable(self) -> Variable {
+        returned(DataType::Float, self.to_string())
+    }
+}
+
+impl FromVariable for Variable {
+    fn from_variable(variable: &Variable) -> Result<Self, DataType> {
+        Ok(variable.clone())
+    }
+}
+
+impl IntoVariable for Variable {
+    fn into_variable(self) -> Variable {
+        self
+    }
+}
+
+impl IntoVariable for &str {
+    fn into_variable(self) -> Variable {
+        returned(DataType::Str, self.to_string())
+    }
+}
+
+// What a command returns, which nothing ever sees
+impl IntoVariable for () {
+    fn into_variable(self) -> Variable {
+        returned(DataType::Str, String::new())
+    }
+}
+
+// The arguments of a host function as a tuple of Rust values
+pub trait Arguments: Sized {
+    const ARITY: usize;
+
+    fn from_variables(variables: &[Variable]) -> Result<Self, String>;
+}
+
+fn argument<T: FromVariable>(variables: &[Variable], index: usize) -> Result<T, String> {
+    T::from_variable(&variables[index]).map_err(|datatype| {
+        format!(
+            "argument {} must be {}, got {}",
+            index + 1,
+            represent_datatype(datatype),
+            represent_datatype(variables[index].datatype)
+        )
+    })
+}
+
+impl Arguments for () {
+    const ARITY: usize = 0;
+
+    fn from_variables(_: &[Variable]) -> Result<Self, String> {
+        Ok(())
+    }
+}
+
+macro_rules! arguments {
+    ($arity:expr; $($type:ident $index:tt),+) => {
+        impl<$($type: FromVariable),+> Arguments for ($($type,)+) {
+            const ARITY: usize = $arity;
+
+            fn from_variables(variables: &[Variable]) -> Result<Self, String> {
+                Ok(($(argument::<$type>(variables, $index)?,)+))
+            }
+        }
+    };
+}
+
+arguments!(1; A 0);
+arguments!(2; A 0, B 1);
+arguments!(3; A 0, B 1, C 2);
+arguments!(4; A 0, B 1, C 2, D 3);
+
+#[test]
+fn test_run() {
+    use crate::{helper::get_variable, interpreter::interpret_source};
+    use std::cell::RefCell;
+
+    let notified = Rc::new(RefCell::new(Vec::new()));
+    let mut context = Context::default();
+
+    context.host.function("double", |(x,): (i32,)| Ok(x * 2));
+    context
+        .host
+        .function("join", |(a, b): (String, f64)| Ok(format!("{a}{b}")));
+
+    let sink = notified.clone();
+    context
+        .host
+        .command("notify", move |(message,): (String,)| {
+            sink.borrow_mut().push(message);
+            Ok(())
+        });
+
+    let mut variables = Variables::new();
+
+    interpret_source(
+        "let x = 21\nlet y = double (x)\nlet z = join (\"a, b \", 2)\nnotify (\"done\")",
+        "main.slm".into(),
+        &mut variables,
+        &mut context,
+    );
+
+    assert_eq!(context.errors, 0);
+    let y = get_variable("y", &variables).unwrap();
+
+    assert_eq!((y.datatype, y.value.as_str()), (DataType::Int, "42"));
+    assert_eq!(get_variable("z", &variables).unwrap().value, "a, b 2");
+    assert_eq!(*notified.borrow(), vec!["done".to_string()]);
+
+    let double = context.host.get_function("double").unwrap();
+
+    assert_eq!(
+        run(double.clone(), vec!["(\"x\")"], &variables, &mut context),
+        Err("argument 1 must be int, got str".to_string())
+    );
+    assert_eq!(
+        run(double, vec!["(1,", "2)"], &variables, &mut context),
+        Err("expected 1 argument, got 2".to_string())
+    );
+}
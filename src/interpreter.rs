@@ -1,7 +1,29 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::read_to_string,
+    io,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Instant,
+};
+
 use crate::{
     commands::*,
-    helper::{assign, get_variable, throw_error},
-    version::VERSION,
+    debugger::Debugger,
+    helper::{
+        check_arity, coerce, extract_arguments, get_variable, joined, offset_in, parse_datatype,
+        represent_datatype, snippet, split_arguments, suggest, throw_error, traceback,
+        unknown_type,
+    },
+    host::{self, Host},
+    lexer::is_word_character,
+    limits::{Interrupt, Limits},
+    profiler::Profiler,
+    sandbox::Sandbox,
+    stdio::{Io, Terminal},
+    trace::Trace,
+    variables::{Name, Variables},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -17,97 +39,461 @@ pub enum DataType {
 #[derive(Debug, Clone, PartialEq)]
 pub struct Variable {
     pub datatype: DataType,
-    pub identifier: String,
+    pub identifier: Name,
     pub value: String,
 }
 
+// Every command `interpret` dispatches on, besides block calls
+pub const COMMANDS: &[&str] = &[
+    "let",
+    "const",
+    "println",
+    "formatln",
+    "readln",
+    "block",
+    "interpret",
+    "eval",
+    "import",
+    "from",
+    "if",
+    "while",
+    "assert",
+    "assert_eq",
+    "exit",
+];
+
+// How a frame was entered, shown next to it in backtraces
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameKind {
+    File,
+    Interpret,
+    Import,
+    Block,
+    Eval,
+}
+
+impl fmt::Display for FrameKind {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(match self {
+            FrameKind::File => "file",
+            FrameKind::Interpret => "interpret",
+            FrameKind::Import => "import",
+            FrameKind::Block => "block call",
+            FrameKind::Eval => "eval",
+        })
+    }
+}
+
+// A file, block or `eval` that's currently running and the line it's at
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub kind: FrameKind,
+    pub input_name: String,
+    pub line_number: i32,
+}
+
+// What to do with warnings, set with `--quiet-warnings` and `-W`
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Warnings {
+    #[default]
+    Show,
+    Quiet,
+    Error,
+}
+
+// State that lives for a whole run rather than for a single scope of variables
+#[derive(Debug)]
+pub struct Context {
+    pub errors: usize,
+    // Namespaces of every module evaluated so far, keyed by their canonical path
+    pub modules: HashMap<PathBuf, Variables>,
+    // Modules that are still being evaluated, used to catch circular imports
+    pub importing: Vec<PathBuf>,
+    // Files currently being interpreted, the last one is where relative paths start from
+    pub files: Vec<PathBuf>,
+    // Shared library directories searched after the importing file's own directory
+    pub lib_paths: Vec<PathBuf>,
+    // Innermost last, empty in the interactive mode
+    pub frames: Vec<Frame>,
+    // The whole line being run, which errors point into
+    pub line: String,
+    // The text `interpret` is running, all or part of `line`, and where on `line` it
+    // starts, `None` when it isn't on it, like the code of an `eval`
+    pub text: Rc<str>,
+    pub column: Option<usize>,
+    // Where on `line` the error about to be thrown is, set by whatever found it
+    pub error_at: Option<(usize, usize)>,
+    // Where the line being run is, for warnings raised by functions that aren't told
+    pub input_name: String,
+    pub line_number: i32,
+    pub warnings: Warnings,
+    // Set by `silm debug`, asked before every line of a file or block runs
+    pub debugger: Option<Debugger>,
+    // Set by `--trace`, every line is written to it before it runs
+    pub trace: Option<Trace>,
+    // Set by `--profile`, times every line of a file or block and every block call
+    pub profiler: Option<Profiler>,
+    // Functions and commands registered by whoever embeds silm
+    pub host: Host,
+    // Where commands read and write, the terminal unless silm is embedded
+    pub io: Box<dyn Io>,
+    // What scripts aren't allowed to do, set with `--no-fs` and the like
+    pub sandbox: Sandbox,
+    pub limits: Limits,
+    // Lines run so far, counted against `limits.max_steps`
+    pub steps: u64,
+    // When the first line ran, for `limits.timeout`
+    pub started: Option<Instant>,
+    // Lines running inside each other right now, counting `if` and `while` bodies
+    pub nesting: usize,
+    // Bytes of variables outside the scope running now, in the scopes waiting on it
+    // and in loaded modules, counted against `limits.max_memory`
+    pub memory: usize,
+    // Bytes of the scope whose line is running, added to `memory` by any scope it starts
+    pub scope_memory: usize,
+    // Set from outside to stop the script before its next line
+    pub interrupt: Interrupt,
+    // Set once a limit is exceeded or the script is interrupted, after which nothing
+    // else runs
+    pub stopped: bool,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Context {
+            errors: 0,
+            modules: HashMap::new(),
+            importing: Vec::new(),
+            files: Vec::new(),
+            lib_paths: Vec::new(),
+            frames: Vec::new(),
+            line: String::new(),
+            text: Rc::from(""),
+            column: None,
+            error_at: None,
+            input_name: String::new(),
+            line_number: 0,
+            warnings: Warnings::default(),
+            debugger: None,
+            trace: None,
+            profiler: None,
+            host: Host::default(),
+            io: Box::new(Terminal::default()),
+            sandbox: Sandbox::default(),
+            limits: Limits::default(),
+            steps: 0,
+            started: None,
+            nesting: 0,
+            memory: 0,
+            scope_memory: 0,
+            interrupt: Interrupt::default(),
+            stopped: false,
+        }
+    }
+}
+
+impl Context {
+    pub fn push_frame(&mut self, kind: FrameKind, input_name: &str, line_number: i32) {
+        self.frames.push(Frame {
+            kind,
+            input_name: input_name.to_string(),
+            line_number,
+        });
+    }
+
+    // Where `part` is on `line`, when it's a slice of `text` rather than a copy
+    pub fn span_of(&self, part: &str) -> Option<(usize, usize)> {
+        let start = self.column? + offset_in(&self.text, part)?;
+
+        Some((start, start + part.len()))
+    }
+
+    // Lets the next input run after a limit or an interrupt stopped the last one
+    pub fn resume(&mut self) {
+        self.steps = 0;
+        self.started = None;
+        self.stopped = false;
+        self.interrupt.clear();
+    }
+
+    // Forgets everything earlier input left behind, for `:reset`, while keeping the
+    // settings like the limits, the sandbox and the library paths
+    pub fn reset(&mut self) {
+        self.errors = 0;
+        self.modules.clear();
+        self.importing.clear();
+        self.frames.clear();
+        self.memory = 0;
+        self.scope_memory = 0;
+        self.resume();
+    }
+
+    // Counts a line or loop iteration and checks it's within the limits
+    pub fn step(&mut self) -> Result<(), String> {
+        if self.interrupt.is_interrupted() {
+            return Err("interrupted".to_string());
+        }
+
+        self.steps += 1;
+
+        let started = *self.started.get_or_insert_with(Instant::now);
+
+        self.limits.check_steps(self.steps)?;
+        self.limits.check_depth(self.frames.len())?;
+        self.limits.check_nesting(self.nesting)?;
+        self.limits.check_time(started)
+    }
+}
+
+#[test]
+fn test_reset() {
+    use crate::stdio::MemoryIo;
+    use std::{env::temp_dir, fs};
+
+    let module = temp_dir().join(format!("silm_reset_{}.slm", std::process::id()));
+    fs::write(&module, "println (\"loaded\")\nlet value = 1").unwrap();
+
+    let io = MemoryIo::new("");
+    let mut context = Context {
+        io: Box::new(io.clone()),
+        ..Default::default()
+    };
+    let import = format!("import (\"{}\") as module", module.display());
+
+    interpret(
+        import.clone(),
+        "<stdin>".into(),
+        0,
+        &mut Variables::new(),
+        &mut context,
+    );
+    interpret(
+        import.clone(),
+        "<stdin>".into(),
+        0,
+        &mut Variables::new(),
+        &mut context,
+    );
+    assert_eq!(io.stdout_text().matches("loaded").count(), 1);
+
+    // Importing again after a reset runs the module again
+    context.reset();
+    assert!(context.modules.is_empty());
+    assert_eq!(context.memory, 0);
+
+    interpret(
+        import,
+        "<stdin>".into(),
+        0,
+        &mut Variables::new(),
+        &mut context,
+    );
+    assert_eq!(io.stdout_text().matches("loaded").count(), 2);
+
+    fs::remove_file(&module).unwrap();
+}
+
 pub fn interpret(
     line: String,
     input_name: String,
     line_number: i32,
-    variables: &mut Vec<Variable>,
+    variables: &mut Variables,
+    context: &mut Context,
 ) {
-    let tokens: Vec<&str> = line.split_whitespace().collect();
-
-    assign(
-        Variable {
-            identifier: "$version$".into(),
-            datatype: DataType::Str,
-            value: VERSION.into(),
-        },
-        variables,
-    );
+    // A limit was exceeded or the script was interrupted, so nothing runs anymore
+    if context.stopped {
+        return;
+    }
 
-    assign(
-        Variable {
-            identifier: "$line$".into(),
-            datatype: DataType::Str,
-            value: line.clone(),
-        },
-        variables,
-    );
+    // Slices of what `context.text` holds while the line runs, so they can be found on it
+    let text: Rc<str> = Rc::from(line.as_str());
+    let tokens: Vec<&str> = text.split_whitespace().collect();
 
-    assign(
-        Variable {
-            identifier: "$input_name$".into(),
-            datatype: DataType::Str,
-            value: input_name.clone(),
-        },
-        variables,
-    );
+    // A line of its own when nothing else is running, files and blocks set the line
+    // themselves and `interpret_part` says where on it the part starts, anything else
+    // isn't on the line
+    let column = if context.frames.is_empty() && context.text.is_empty() {
+        context.line = line.clone();
+        Some(0)
+    } else if line == context.line {
+        Some(0)
+    } else {
+        context
+            .column
+            .filter(|&column| context.line.get(column..column + line.len()) == Some(line.as_str()))
+    };
 
-    assign(
-        Variable {
-            identifier: "$line_number$".into(),
-            datatype: DataType::Int,
-            value: line_number.to_string(),
-        },
-        variables,
-    );
+    if let Some(trace) = &mut context.trace {
+        trace.line(&line, &input_name, line_number, context.frames.len());
+    }
 
-    if !line.trim().starts_with('#') && !tokens.is_empty() {
+    // Put back once the line is done, since it might have called a block
+    let caller_input_name = std::mem::replace(&mut context.input_name, input_name.clone());
+    let caller_line_number = std::mem::replace(&mut context.line_number, line_number);
+    let caller_text = std::mem::replace(&mut context.text, text.clone());
+    let caller_column = std::mem::replace(&mut context.column, column);
+    context.error_at = None;
+    context.nesting += 1;
+    context.scope_memory = variables.bytes();
+
+    // Commands the sandbox doesn't allow never get to run
+    let permission = match tokens.first() {
+        Some(command) => context.sandbox.check_name(command, &context.host),
+        None => Ok(()),
+    };
+
+    let is_code = !line.trim().starts_with('#') && !tokens.is_empty();
+    let limit = if is_code { context.step() } else { Ok(()) };
+
+    if let Err(error) = permission {
+        throw_error(&error, tokens[0], input_name, line_number, context);
+    } else if let Err(error) = limit {
+        throw_error(&error, tokens[0], input_name, line_number, context);
+        context.stopped = true;
+    } else if is_code {
         match tokens[0] {
-            "let" => silm_let(tokens[1..].to_vec(), input_name, line_number, variables),
+            "let" => silm_let(
+                tokens[1..].to_vec(),
+                input_name,
+                line_number,
+                variables,
+                context,
+            ),
+
+            "const" => silm_const(
+                tokens[1..].to_vec(),
+                input_name,
+                line_number,
+                variables,
+                context,
+            ),
+
+            "println" => silm_println(
+                tokens[1..].to_vec(),
+                input_name,
+                line_number,
+                variables,
+                context,
+            ),
 
-            "println" => silm_println(tokens[1..].to_vec(), input_name, line_number, variables),
+            "formatln" => silm_formatln(
+                tokens[1..].to_vec(),
+                input_name,
+                line_number,
+                variables,
+                context,
+            ),
 
-            "formatln" => silm_formatln(tokens[1..].to_vec(), input_name, line_number, variables),
+            "readln" => silm_readln(
+                tokens[1..].to_vec(),
+                input_name,
+                line_number,
+                variables,
+                context,
+            ),
 
-            "readln" => silm_readln(tokens[1..].to_vec(), input_name, line_number, variables),
+            "block" => silm_block(
+                tokens[1..].to_vec(),
+                input_name,
+                line_number,
+                variables,
+                context,
+            ),
 
-            "block" => silm_block(tokens[1..].to_vec(), input_name, line_number, variables),
+            "interpret" => silm_interpret(
+                tokens[1..].to_vec(),
+                input_name,
+                line_number,
+                variables,
+                context,
+            ),
 
-            "interpret" => silm_interpret(tokens[1..].to_vec(), input_name, line_number, variables),
+            "eval" => silm_eval(
+                tokens[1..].to_vec(),
+                input_name,
+                line_number,
+                variables,
+                context,
+            ),
 
-            "eval" => silm_eval(tokens[1..].to_vec(), input_name, line_number, variables),
+            "import" => silm_import(
+                tokens[1..].to_vec(),
+                input_name,
+                line_number,
+                variables,
+                context,
+            ),
 
-            "import" => silm_import(tokens[1..].to_vec(), input_name, line_number, variables),
+            "from" => silm_from(
+                tokens[1..].to_vec(),
+                input_name,
+                line_number,
+                variables,
+                context,
+            ),
 
-            "if" => silm_if(tokens[1..].to_vec(), input_name, line_number, variables),
+            "if" => silm_if(
+                tokens[1..].to_vec(),
+                input_name,
+                line_number,
+                variables,
+                context,
+            ),
 
-            "while" => silm_while(tokens[1..].to_vec(), input_name, line_number, variables),
+            "while" => silm_while(
+                tokens[1..].to_vec(),
+                input_name,
+                line_number,
+                variables,
+                context,
+            ),
 
-            "exit" => silm_exit(tokens[1..].to_vec(), input_name, line_number),
+            "assert" => silm_assert(
+                tokens[1..].to_vec(),
+                input_name,
+                line_number,
+                variables,
+                context,
+            ),
+
+            "assert_eq" => silm_assert_eq(
+                tokens[1..].to_vec(),
+                input_name,
+                line_number,
+                variables,
+                context,
+            ),
+
+            "exit" => silm_exit(tokens[1..].to_vec(), input_name, line_number, context),
 
             "" => {}
 
             _ => {
-                if let Some(variable) = get_variable(tokens[0], variables) {
+                if let Some(command) = context.host.get_command(tokens[0]) {
+                    if let Err(error) = host::run(command, tokens[1..].to_vec(), variables, context)
+                    {
+                        throw_error(&error, tokens[0], input_name, line_number, context);
+                    }
+                } else if let Some(variable) = get_variable(tokens[0], variables) {
                     if variable.datatype == DataType::Block {
                         if tokens.len() >= 2 {
-                            if tokens[1] == "()" {
-                                let mut block_variables: Vec<Variable> = Vec::new();
-                                let mut current_line = 0;
-
-                                for line in variable.value.lines() {
-                                    current_line += 1;
-
-                                    interpret(
-                                        line.to_string(),
-                                        format!("<block {}>", variable.identifier),
-                                        current_line,
-                                        &mut block_variables,
-                                    )
+                            let arguments = joined(&tokens[1..], &text);
+
+                            if let Some(arguments) = arguments
+                                .strip_prefix('(')
+                                .and_then(|arguments| arguments.strip_suffix(')'))
+                            {
+                                let called = extract_arguments(arguments, variables, context)
+                                    .and_then(|arguments| call_block(variable, arguments, context));
+
+                                if let Err(error) = called {
+                                    throw_error(
+                                        &error,
+                                        &variable.identifier,
+                                        input_name,
+                                        line_number,
+                                        context,
+                                    );
                                 }
                             } else {
                                 throw_error(
@@ -115,6 +501,7 @@ pub fn interpret(
                                     &variable.identifier,
                                     input_name,
                                     line_number,
+                                    context,
                                 );
                             }
                         } else {
@@ -123,25 +510,314 @@ pub fn interpret(
                                 &variable.identifier,
                                 input_name,
                                 line_number,
+                                context,
                             );
                         }
                     } else {
-                        println!(
-                            "{}:{}: unrecognised command: {}",
-                            input_name,
-                            line_number,
-                            tokens.join(" ")
-                        );
+                        unrecognised_command(&tokens, &input_name, line_number, variables, context);
                     }
                 } else {
-                    println!(
-                        "{}:{}: unrecognised command: {}",
-                        input_name,
-                        line_number,
-                        tokens.join(" ")
-                    );
+                    unrecognised_command(&tokens, &input_name, line_number, variables, context);
                 }
             }
         }
+
+        // Checked after every line, unless a line it ran already stopped everything
+        let memory = if context.stopped {
+            Ok(())
+        } else {
+            context
+                .limits
+                .check_memory(context.memory + variables.bytes())
+        };
+
+        if let Err(error) = memory {
+            let input_name = context.input_name.clone();
+
+            throw_error(&error, tokens[0], input_name, line_number, context);
+            context.stopped = true;
+        }
+    }
+
+    context.input_name = caller_input_name;
+    context.line_number = caller_line_number;
+    context.text = caller_text;
+    context.column = caller_column;
+    context.nesting -= 1;
+}
+
+// Runs part of the line being run, like the code after an `if`'s `::`, telling
+// `interpret` where it is so errors in it still point at the right place
+pub fn interpret_part(
+    part: &str,
+    input_name: String,
+    line_number: i32,
+    variables: &mut Variables,
+    context: &mut Context,
+) {
+    let column = context.column;
+
+    context.column = context.span_of(part).map(|(start, _)| start);
+    interpret(
+        part.to_string(),
+        input_name,
+        line_number,
+        variables,
+        context,
+    );
+    context.column = column;
+}
+
+fn unrecognised_command(
+    tokens: &[&str],
+    input_name: &str,
+    line_number: i32,
+    variables: &Variables,
+    context: &mut Context,
+) {
+    context.errors += 1;
+
+    // Commands and blocks are the only things a line can start with
+    let blocks = variables
+        .iter()
+        .filter(|variable| variable.datatype == DataType::Block)
+        .map(|variable| variable.identifier.as_str());
+
+    let _ = writeln!(
+        context.io.stdout(),
+        "{}:{}: unrecognised command: {}{}",
+        input_name,
+        line_number,
+        tokens.join(" "),
+        match suggest(tokens[0], COMMANDS.iter().copied().chain(blocks)) {
+            Some(suggestion) => format!(", did you mean `{}`?", suggestion),
+            None => String::new(),
+        }
+    );
+
+    if let Some((column, end)) = context.span_of(tokens[0]) {
+        let snippet = snippet(&context.line, line_number, column, end);
+        let _ = write!(context.io.stdout(), "{}", snippet);
+    }
+
+    let traceback = traceback(&context.frames);
+    let _ = write!(context.io.stdout(), "{}", traceback);
+}
+
+// Runs every line of `source` one after the other, like a file
+pub fn interpret_source(
+    source: &str,
+    input_name: String,
+    variables: &mut Variables,
+    context: &mut Context,
+) {
+    let mut line_number = 0;
+    let caller_line = std::mem::take(&mut context.line);
+
+    for line in source.lines() {
+        if context.stopped {
+            break;
+        }
+
+        line_number += 1;
+        context.line = line.to_string();
+
+        if let Some(frame) = context.frames.last_mut() {
+            frame.line_number = line_number;
+        }
+
+        if let Some(mut debugger) = context.debugger.take() {
+            debugger.pause(line, variables, context);
+            context.debugger = Some(debugger);
+        }
+
+        if let Some(profiler) = &mut context.profiler {
+            profiler.start_line();
+        }
+
+        interpret(
+            line.to_string(),
+            input_name.clone(),
+            line_number,
+            variables,
+            context,
+        );
+
+        if let Some(profiler) = &mut context.profiler {
+            profiler.end_line(line, &input_name, line_number, &context.frames);
+        }
+    }
+
+    context.line = caller_line;
+}
+
+// Runs a whole file, keeping track of it so paths inside it resolve relative to it
+pub fn interpret_file(
+    path: &Path,
+    input_name: String,
+    kind: FrameKind,
+    variables: &mut Variables,
+    context: &mut Context,
+) -> io::Result<()> {
+    let source = read_to_string(path)?;
+    interpret_file_source(path, &source, input_name, kind, variables, context);
+
+    Ok(())
+}
+
+// `interpret_file` for a file that's already been read
+pub fn interpret_file_source(
+    path: &Path,
+    source: &str,
+    input_name: String,
+    kind: FrameKind,
+    variables: &mut Variables,
+    context: &mut Context,
+) {
+    context.files.push(path.to_path_buf());
+    context.push_frame(kind, &input_name, 0);
+    interpret_source(source, input_name, variables, context);
+    context.frames.pop();
+    context.files.pop();
+}
+
+// A block's parameter, `name` or `name: type`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    pub datatype: Option<DataType>,
+}
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.datatype {
+            Some(datatype) => write!(formatter, "{}: {}", self.name, represent_datatype(datatype)),
+            None => write!(formatter, "{}", self.name),
+        }
+    }
+}
+
+// The parameters between a block's name and `::`, without their parantheses
+pub fn parse_parameters(text: &str) -> Result<Vec<Parameter>, String> {
+    let mut parameters: Vec<Parameter> = Vec::new();
+
+    for (index, parameter) in split_arguments(text).into_iter().enumerate() {
+        let (name, datatype) = match parameter.split_once(':') {
+            Some((name, datatype)) => match parse_datatype(datatype.trim()) {
+                Some(datatype) => (name.trim(), Some(datatype)),
+                None => return Err(unknown_type(datatype.trim())),
+            },
+
+            None => (parameter, None),
+        };
+
+        if name.is_empty() || !name.chars().all(is_word_character) {
+            return Err(format!("parameter {} must be a name", index + 1));
+        }
+
+        if parameters.iter().any(|parameter| parameter.name == name) {
+            return Err(format!("parameter '{}' is given twice", name));
+        }
+
+        parameters.push(Parameter {
+            name: name.to_string(),
+            datatype,
+        });
+    }
+
+    Ok(parameters)
+}
+
+// Blocks with parameters keep them on the first line of their value, like `(a: int, b)`
+pub fn block_parameters(block: &Variable) -> (Vec<Parameter>, &str) {
+    let (first_line, rest) = block.value.split_once('\n').unwrap_or((&block.value, ""));
+
+    let parameters = first_line
+        .strip_prefix('(')
+        .and_then(|header| header.strip_suffix(')'))
+        .and_then(|header| parse_parameters(header).ok());
+
+    match parameters {
+        Some(parameters) => (parameters, rest),
+        None => (Vec::new(), &block.value),
+    }
+}
+
+#[test]
+fn test_block_parameters() {
+    let block = Variable {
+        datatype: DataType::Block,
+        identifier: "greet".into(),
+        value: "(name: str, times)\nprintln (name)".into(),
+    };
+
+    let (parameters, body) = block_parameters(&block);
+
+    assert_eq!(
+        parameters
+            .iter()
+            .map(|parameter| parameter.to_string())
+            .collect::<Vec<_>>(),
+        vec!["name: str", "times"]
+    );
+    assert_eq!(body, "println (name)");
+
+    assert_eq!(parse_parameters("a: list"), Err(unknown_type("list")));
+    assert_eq!(
+        parse_parameters("a, a"),
+        Err("parameter 'a' is given twice".to_string())
+    );
+    assert_eq!(
+        parse_parameters("a, (b)"),
+        Err("parameter 2 must be a name".to_string())
+    );
+}
+
+// Blocks don't see the caller's variables, they only start with their arguments
+pub fn call_block(
+    block: &Variable,
+    arguments: Vec<Variable>,
+    context: &mut Context,
+) -> Result<(), String> {
+    let (parameters, body) = block_parameters(block);
+    check_arity(parameters.len(), arguments.len())?;
+
+    let mut block_variables: Variables = Variables::new();
+
+    for (parameter, argument) in parameters.iter().zip(arguments) {
+        let variable = Variable {
+            identifier: parameter.name.as_str().into(),
+            datatype: argument.datatype,
+            value: argument.value,
+        };
+
+        match parameter.datatype {
+            Some(datatype) => {
+                block_variables.assign(coerce(variable, datatype)?);
+                block_variables.annotate(&parameter.name, datatype);
+            }
+
+            None => block_variables.assign(variable),
+        }
+    }
+
+    let input_name = format!("<block {}>", block.identifier);
+
+    let started = Instant::now();
+
+    // The caller's variables stay alive while the block runs, its own go once it's done
+    let caller_memory = (context.memory, context.scope_memory);
+    context.memory += context.scope_memory;
+
+    context.push_frame(FrameKind::Block, &input_name, 0);
+    interpret_source(body, input_name, &mut block_variables, context);
+    context.frames.pop();
+
+    (context.memory, context.scope_memory) = caller_memory;
+
+    if let Some(profiler) = &mut context.profiler {
+        profiler.block(&block.identifier, started.elapsed());
     }
+
+    Ok(())
 }
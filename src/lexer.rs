@@ -0,0 +1,252 @@
+// Splits a line into tokens with their columns, for the tools that look at code
+// without running it. `interpret` itself still works on whitespace-split tokens
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    Word,
+    Int,
+    Float,
+    Str,
+    Char,
+    LeftParen,
+    RightParen,
+    Comma,
+    Operator,
+    Assign,
+    // For type annotations, `name: int`
+    Colon,
+    Colons,
+    Separator,
+    Comment,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    // Byte offset of the token in the line it came from
+    pub column: usize,
+    // Whether whitespace (or the start of the line) comes right before the token,
+    // which matters since `interpret` splits lines on whitespace
+    pub spaced: bool,
+}
+
+impl Token<'_> {
+    pub fn end(&self) -> usize {
+        self.column + self.text.len()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub column: usize,
+    pub end: usize,
+    pub message: String,
+}
+
+impl SyntaxError {
+    pub fn new(column: usize, end: usize, message: impl Into<String>) -> Self {
+        SyntaxError {
+            column,
+            end,
+            message: message.into(),
+        }
+    }
+}
+
+pub fn tokenize(line: &str) -> Result<Vec<Token<'_>>, SyntaxError> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut characters = line.char_indices().peekable();
+    let mut spaced = true;
+
+    while let Some((start, character)) = characters.next() {
+        if character.is_whitespace() {
+            spaced = true;
+            continue;
+        }
+
+        let kind = match character {
+            '#' => {
+                while characters.next().is_some() {}
+                TokenKind::Comment
+            }
+
+            '"' | '\'' => {
+                let mut terminated = false;
+
+                for (_, next) in characters.by_ref() {
+                    if next == character {
+                        terminated = true;
+                        break;
+                    }
+                }
+
+                if !terminated {
+                    return Err(SyntaxError::new(
+                        start,
+                        line.len(),
+                        if character == '"' {
+                            "unterminated string"
+                        } else {
+                            "unterminated char"
+                        },
+                    ));
+                }
+
+                if character == '"' {
+                    TokenKind::Str
+                } else {
+                    TokenKind::Char
+                }
+            }
+
+            '(' => TokenKind::LeftParen,
+
+            ')' => TokenKind::RightParen,
+
+            ',' => TokenKind::Comma,
+
+            '=' => TokenKind::Assign,
+
+            ':' => match characters.peek() {
+                Some((_, ':')) => {
+                    characters.next();
+                    TokenKind::Colons
+                }
+
+                _ => TokenKind::Colon,
+            },
+
+            '\\' => match characters.peek() {
+                Some((_, ';')) => {
+                    characters.next();
+                    TokenKind::Separator
+                }
+
+                _ => TokenKind::Unknown,
+            },
+
+            '-' if characters
+                .peek()
+                .is_some_and(|(_, next)| next.is_ascii_digit())
+                && expects_value(tokens.last()) =>
+            {
+                number(&mut characters)
+            }
+
+            '+' | '-' | '*' | '/' | '%' | '^' => TokenKind::Operator,
+
+            _ if character.is_ascii_digit() => number(&mut characters),
+
+            _ if is_word_character(character) => {
+                while characters
+                    .peek()
+                    .is_some_and(|(_, next)| is_word_character(*next))
+                {
+                    characters.next();
+                }
+
+                TokenKind::Word
+            }
+
+            _ => TokenKind::Unknown,
+        };
+
+        let end = match characters.peek() {
+            Some((index, _)) => *index,
+            None => line.len(),
+        };
+
+        tokens.push(Token {
+            kind,
+            text: &line[start..end],
+            column: start,
+            spaced,
+        });
+
+        spaced = false;
+    }
+
+    Ok(tokens)
+}
+
+#[test]
+fn test_tokenize() {
+    let tokens = tokenize("let x = -1.5 - y").unwrap();
+    let kinds: Vec<TokenKind> = tokens.iter().map(|token| token.kind).collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            TokenKind::Word,
+            TokenKind::Word,
+            TokenKind::Assign,
+            TokenKind::Float,
+            TokenKind::Operator,
+            TokenKind::Word
+        ]
+    );
+    assert_eq!(tokens[3].text, "-1.5");
+    assert_eq!(tokens[5].column, 15);
+
+    let tokens = tokenize("println(\"a \\; b\") \\; # done").unwrap();
+
+    assert_eq!(tokens[1].kind, TokenKind::LeftParen);
+    assert!(!tokens[1].spaced);
+    assert_eq!(tokens[2].text, "\"a \\; b\"");
+    assert_eq!(tokens[4].kind, TokenKind::Separator);
+    assert_eq!(tokens[5].kind, TokenKind::Comment);
+
+    let tokens = tokenize("let x: int = 1").unwrap();
+
+    assert_eq!(tokens[2].kind, TokenKind::Colon);
+    assert!(!tokens[2].spaced);
+
+    assert_eq!(
+        tokenize("println (\"oops)"),
+        Err(SyntaxError::new(9, 15, "unterminated string"))
+    );
+}
+
+// Identifiers can contain dots for module members and dollar signs for magic variables
+pub fn is_word_character(character: char) -> bool {
+    character.is_alphanumeric() || character == '_' || character == '.' || character == '$'
+}
+
+// A minus sign is part of a number unless it comes right after a value
+fn expects_value(previous: Option<&Token>) -> bool {
+    match previous {
+        Some(token) => matches!(
+            token.kind,
+            TokenKind::Operator
+                | TokenKind::LeftParen
+                | TokenKind::Comma
+                | TokenKind::Assign
+                | TokenKind::Colons
+                | TokenKind::Separator
+        ),
+        None => true,
+    }
+}
+
+fn number(characters: &mut std::iter::Peekable<std::str::CharIndices>) -> TokenKind {
+    let mut kind = TokenKind::Int;
+
+    while let Some((_, next)) = characters.peek() {
+        if next.is_ascii_digit() {
+            characters.next();
+        } else if *next == '.' {
+            kind = TokenKind::Float;
+            characters.next();
+        } else if is_word_character(*next) {
+            // Something like `1abc` that can't be read as anything
+            kind = TokenKind::Unknown;
+            characters.next();
+        } else {
+            break;
+        }
+    }
+
+    kind
+}
@@ -0,0 +1,22 @@
+pub mod checker;
+pub mod commands;
+pub mod debugger;
+pub mod formatter;
+pub mod functions;
+pub mod helper;
+pub mod host;
+pub mod interpreter;
+pub mod lexer;
+pub mod limits;
+pub mod linter;
+pub mod lsp;
+pub mod modules;
+pub mod parser;
+pub mod profiler;
+pub mod repl;
+pub mod sandbox;
+pub mod stdio;
+pub mod testing;
+pub mod trace;
+pub mod variables;
+pub mod version;
@@ -0,0 +1,170 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+// Deep enough for any sensible script, shallow enough that `eval` or `interpret`
+// calling itself stops with an error instead of overflowing the stack
+pub const DEFAULT_MAX_DEPTH: usize = 100;
+
+// The most `--max-depth` allows, every level takes a few stack frames and debug builds
+// overflow the stack somewhere past 1000
+pub const MAX_DEPTH: usize = 500;
+
+// How many lines can run inside each other whatever the limits say, since `if` and
+// `while` bodies don't count towards `--max-depth` but take just as much stack
+pub const MAX_NESTING: usize = 1000;
+
+// How much a script may do before it's stopped with an error, `None` means no limit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    // Lines run, counting every line of a loop or block each time it runs
+    pub max_steps: Option<u64>,
+    // Blocks, `eval`s, `interpret`s and imports running inside each other
+    pub max_depth: Option<usize>,
+    // Bytes taken by the names and values of every variable alive, in the running
+    // scope, the scopes waiting on it and loaded modules
+    pub max_memory: Option<usize>,
+    // How long a script may run, counted from its first line
+    pub timeout: Option<Duration>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_steps: None,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_memory: None,
+            timeout: None,
+        }
+    }
+}
+
+impl Limits {
+    pub fn check_steps(&self, steps: u64) -> Result<(), String> {
+        match self.max_steps {
+            Some(max_steps) if steps > max_steps => Err(format!(
+                "limit exceeded: ran more than {} lines (--max-steps)",
+                max_steps
+            )),
+
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_depth(&self, depth: usize) -> Result<(), String> {
+        match self.max_depth {
+            Some(max_depth) if depth > max_depth => Err(format!(
+                "limit exceeded: more than {} nested calls (--max-depth)",
+                max_depth
+            )),
+
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_nesting(&self, nesting: usize) -> Result<(), String> {
+        if nesting > MAX_NESTING {
+            Err(format!(
+                "limit exceeded: code nested more than {} deep",
+                MAX_NESTING
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn check_time(&self, started: Instant) -> Result<(), String> {
+        match self.timeout {
+            Some(timeout) if started.elapsed() > timeout => Err(format!(
+                "limit exceeded: ran for more than {:?} (--timeout)",
+                timeout
+            )),
+
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_memory(&self, bytes: usize) -> Result<(), String> {
+        let max_memory = match self.max_memory {
+            Some(max_memory) => max_memory,
+            None => return Ok(()),
+        };
+
+        if bytes > max_memory {
+            Err(format!(
+                "limit exceeded: variables take more than {} bytes (--max-memory)",
+                max_memory
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+// `--timeout`'s seconds, `None` for anything that isn't a duration, like a negative
+// number, `inf` or more seconds than a `Duration` holds
+pub fn parse_timeout(seconds: &str) -> Option<Duration> {
+    seconds
+        .parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+}
+
+#[test]
+fn test_parse_timeout() {
+    assert_eq!(parse_timeout("1.5"), Some(Duration::from_millis(1500)));
+    assert_eq!(parse_timeout("0"), Some(Duration::ZERO));
+
+    for invalid in ["-1", "inf", "NaN", "1e30", "soon"] {
+        assert_eq!(parse_timeout(invalid), None, "{}", invalid);
+    }
+}
+
+// Stops a script from another thread, or from a Ctrl-C handler, before its next line
+// or loop iteration. Clones share the same flag
+#[derive(Debug, Clone, Default)]
+pub struct Interrupt(Arc<AtomicBool>);
+
+impl Interrupt {
+    pub fn interrupt(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn clear(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_check_nesting() {
+    let limits = Limits {
+        max_depth: None,
+        ..Default::default()
+    };
+
+    assert_eq!(limits.check_nesting(MAX_NESTING), Ok(()));
+    assert!(limits.check_nesting(MAX_NESTING + 1).is_err());
+}
+
+#[test]
+fn test_check_memory() {
+    let limits = Limits {
+        max_memory: Some(8),
+        ..Default::default()
+    };
+
+    assert_eq!(limits.check_memory(8), Ok(()));
+    assert_eq!(
+        limits.check_memory(9),
+        Err("limit exceeded: variables take more than 8 bytes (--max-memory)".to_string())
+    );
+    assert_eq!(Limits::default().check_memory(usize::MAX), Ok(()));
+}
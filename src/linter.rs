@@ -0,0 +1,525 @@
+use std::collections::{HashMap, HashSet};
+
+use serde_json::{json, Value};
+
+use crate::{
+    helper::{comparable, represent_datatype, suggest},
+    interpreter::DataType,
+    modules::module_name,
+    parser::{parse_source, Expression, Node, Span, Statement},
+};
+
+// Every rule with what it catches, a rule can be turned off for the next
+// line with a `# silm: allow(rule)` comment
+pub const RULES: &[(&str, &str)] = &[
+    (
+        "undefined-variable",
+        "a variable is used before any `let` defines it, blocks don't see their caller's variables",
+    ),
+    (
+        "mismatched-eq",
+        "`eq` or `ne` compares datatypes that are never equal, like an int and a str, which is always false (or true)",
+    ),
+    (
+        "unreachable-code",
+        "code that comes after `exit ()` and can never run",
+    ),
+    (
+        "unused-variable",
+        "a variable defined inside a block that's never used there",
+    ),
+    (
+        "reassigned-constant",
+        "a name bound with `const` is assigned again, which fails when it runs",
+    ),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lint {
+    pub rule: &'static str,
+    pub line_number: i32,
+    pub column: usize,
+    pub end: usize,
+    pub message: String,
+}
+
+impl Lint {
+    pub fn to_json(&self, file: &str) -> Value {
+        json!({
+            "file": file,
+            "line": self.line_number,
+            "column": self.column + 1,
+            "end_column": self.end + 1,
+            "rule": self.rule,
+            "message": self.message,
+        })
+    }
+}
+
+// Where a name gets its value, for editors to jump to and hover over
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub name: String,
+    pub line_number: i32,
+    pub span: Span,
+    pub datatype: Option<DataType>,
+}
+
+#[derive(Default)]
+struct Scope {
+    // Defined variables with their datatype when it's known
+    defined: HashMap<String, Option<DataType>>,
+    // Names of imported modules, anything inside them is considered defined
+    namespaces: Vec<String>,
+    // Set once `eval` or `interpret` runs, after which anything could be defined
+    dynamic: bool,
+    used: HashSet<String>,
+    // Variables defined with `let` and where, for `unused-variable`
+    lets: Vec<(String, i32, Span)>,
+    constants: HashSet<String>,
+    definitions: Vec<Definition>,
+}
+
+impl Scope {
+    fn is_defined(&self, name: &str) -> bool {
+        self.dynamic
+            || name.starts_with('$')
+            || self.defined.contains_key(name)
+            || self
+                .namespaces
+                .iter()
+                .any(|namespace| name.starts_with(&format!("{}.", namespace)))
+    }
+
+    fn define(&mut self, name: &str, line_number: i32, span: Span, datatype: Option<DataType>) {
+        self.defined.insert(name.to_string(), datatype);
+        self.definitions.push(Definition {
+            name: name.to_string(),
+            line_number,
+            span,
+            datatype,
+        });
+    }
+}
+
+pub fn lint_source(source: &str) -> Vec<Lint> {
+    analyse(source).0
+}
+
+// Every `let`, `block` and `from` name in the source, including the ones inside blocks
+pub fn definitions(source: &str) -> Vec<Definition> {
+    analyse(source).1
+}
+
+fn analyse(source: &str) -> (Vec<Lint>, Vec<Definition>) {
+    let mut lints: Vec<Lint> = Vec::new();
+    let mut allowed: HashMap<i32, Vec<String>> = HashMap::new();
+    let mut pending: Vec<String> = Vec::new();
+    let mut scope = Scope::default();
+    let mut exited = false;
+    let mut unreachable = false;
+
+    for parsed_line in parse_source(source) {
+        let line_number = parsed_line.line_number;
+
+        match &parsed_line.node.statement {
+            Statement::Comment => {
+                let line = source.lines().nth(line_number as usize - 1).unwrap_or("");
+                pending.extend(allow_directive(line));
+                continue;
+            }
+
+            Statement::Empty => continue,
+
+            _ => allowed.insert(line_number, std::mem::take(&mut pending)),
+        };
+
+        // Lines with syntax errors are `silm check`'s business
+        if !parsed_line.errors.is_empty() {
+            continue;
+        }
+
+        // Once is enough, everything after it is just as unreachable, but it's still
+        // checked like the rest of the file
+        if exited && !unreachable {
+            lints.push(Lint {
+                rule: "unreachable-code",
+                line_number,
+                column: parsed_line.node.span.start,
+                end: parsed_line.node.span.end,
+                message: "unreachable code after `exit ()`".into(),
+            });
+
+            unreachable = true;
+        }
+
+        if statement(&parsed_line.node, line_number, &mut scope, &mut lints) {
+            exited = true;
+        }
+    }
+
+    lints.retain(|lint| {
+        !allowed
+            .get(&lint.line_number)
+            .is_some_and(|rules| rules.iter().any(|rule| rule == lint.rule))
+    });
+
+    lints.sort_by_key(|lint| (lint.line_number, lint.column));
+    (lints, scope.definitions)
+}
+
+#[test]
+fn test_lint_source() {
+    let rules = |source: &str| -> Vec<(&'static str, i32)> {
+        lint_source(source)
+            .into_iter()
+            .map(|lint| (lint.rule, lint.line_number))
+            .collect()
+    };
+
+    assert_eq!(
+        rules("println (x)\nlet x = 1\nprintln (x)"),
+        vec![("undefined-variable", 1)]
+    );
+    assert_eq!(
+        rules("let x = \"3\"\nif (eq (x, 3)) :: println (x)"),
+        vec![("mismatched-eq", 2)]
+    );
+    assert!(rules("let x = 1 + 2\nif (eq (x, 3)) :: println (x)").is_empty());
+    assert_eq!(
+        rules("exit ()\n\nprintln (\"never\")\nprintln (\"again\")"),
+        vec![("unreachable-code", 3)]
+    );
+    assert_eq!(
+        rules("exit ()\nprintln (nope)\nexit ()\nprintln (\"again\")"),
+        vec![("unreachable-code", 2), ("undefined-variable", 2)]
+    );
+    assert_eq!(
+        rules("let outside = 1\nblock b :: let unused = 1 \\; println (outside)"),
+        vec![("unused-variable", 2), ("undefined-variable", 2)]
+    );
+    assert_eq!(
+        rules("# silm: allow(undefined-variable)\nprintln (x)\nprintln (y)"),
+        vec![("undefined-variable", 3)]
+    );
+    assert!(
+        rules("import (\"modules/secret.slm\")\nprintln (secret.secret_passphrase)").is_empty()
+    );
+    assert!(rules("eval (\"let x = 1\")\nprintln (x)").is_empty());
+    assert!(rules("block greet (name: str) :: println (name)\ngreet (\"a\")").is_empty());
+    assert_eq!(
+        rules("const x = 1\nlet x = 2\nprintln (x)"),
+        vec![("reassigned-constant", 2)]
+    );
+
+    assert_eq!(
+        lint_source("let message = 1\nprintln (mesage)")[0].message,
+        "variable 'mesage' is used before it's defined, did you mean `message`?"
+    );
+}
+
+// The rules listed in a `# silm: allow(rule, other-rule)` comment
+fn allow_directive(line: &str) -> Vec<String> {
+    let comment = line.trim().trim_start_matches('#').trim();
+
+    match comment
+        .strip_prefix("silm:")
+        .map(|rest| rest.trim())
+        .and_then(|rest| rest.strip_prefix("allow("))
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        Some(rules) => rules
+            .split(',')
+            .map(|rule| rule.trim().to_string())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+// Returns whether the statement exits
+fn statement(node: &Node, line_number: i32, scope: &mut Scope, lints: &mut Vec<Lint>) -> bool {
+    match &node.statement {
+        Statement::Let {
+            name,
+            name_span,
+            annotation,
+            value,
+            constant,
+        } => {
+            let inferred = expression(value, line_number, scope, lints);
+            let datatype = annotation.map(|(datatype, _)| datatype).or(inferred);
+
+            if scope.constants.contains(name) {
+                lints.push(Lint {
+                    rule: "reassigned-constant",
+                    line_number,
+                    column: name_span.start,
+                    end: name_span.end,
+                    message: format!("'{}' is a constant and can't be reassigned", name),
+                });
+            } else if *constant {
+                scope.constants.insert(name.clone());
+            }
+
+            scope.define(name, line_number, *name_span, datatype);
+            scope.lets.push((name.clone(), line_number, *name_span));
+        }
+
+        Statement::Println(value) => {
+            if let Some(value) = value {
+                expression(value, line_number, scope, lints);
+            }
+        }
+
+        Statement::Formatln(value) => {
+            expression(value, line_number, scope, lints);
+            use_placeholders(value, scope);
+        }
+
+        Statement::Readln { prompt, target } => {
+            expression(prompt, line_number, scope, lints);
+            expression(target, line_number, scope, lints);
+
+            if let Expression::Variable(name, _) = target {
+                scope.defined.insert(name.clone(), Some(DataType::Str));
+            }
+        }
+
+        Statement::Block {
+            name,
+            name_span,
+            parameters,
+            body,
+        } => {
+            scope.define(name, line_number, *name_span, Some(DataType::Block));
+            scope.lets.push((name.clone(), line_number, *name_span));
+
+            // Blocks always run with an empty scope of their own
+            let mut block_scope = Scope::default();
+            block_scope
+                .defined
+                .insert(name.clone(), Some(DataType::Block));
+
+            for (parameter, span, datatype) in parameters {
+                block_scope.define(parameter, line_number, *span, *datatype);
+            }
+
+            sequence(body, line_number, &mut block_scope, lints);
+            scope.definitions.append(&mut block_scope.definitions);
+
+            for (variable, variable_line, span) in &block_scope.lets {
+                if variable != name
+                    && !variable.starts_with('_')
+                    && !block_scope.used.contains(variable)
+                    && !block_scope.dynamic
+                {
+                    lints.push(Lint {
+                        rule: "unused-variable",
+                        line_number: *variable_line,
+                        column: span.start,
+                        end: span.end,
+                        message: format!("variable '{}' is never used", variable),
+                    });
+                }
+            }
+        }
+
+        Statement::Interpret(value) | Statement::Eval(value) => {
+            expression(value, line_number, scope, lints);
+            scope.dynamic = true;
+        }
+
+        Statement::Import { path, alias } => {
+            expression(path, line_number, scope, lints);
+
+            let namespace = match (alias, path) {
+                (Some((alias, _)), _) => Some(alias.clone()),
+                (None, Expression::Literal(DataType::Str, filename, _)) => module_name(filename),
+                _ => None,
+            };
+
+            match namespace {
+                Some(namespace) => scope.namespaces.push(namespace),
+                None => scope.dynamic = true,
+            }
+        }
+
+        Statement::From { path, names } => {
+            expression(path, line_number, scope, lints);
+
+            for (name, span) in names {
+                scope.define(name, line_number, *span, None);
+            }
+        }
+
+        Statement::If { condition, body } | Statement::While { condition, body } => {
+            expression(condition, line_number, scope, lints);
+            sequence(body, line_number, scope, lints);
+        }
+
+        Statement::Assert { condition, message } => {
+            expression(condition, line_number, scope, lints);
+
+            if let Some(message) = message {
+                expression(message, line_number, scope, lints);
+            }
+        }
+
+        Statement::AssertEq {
+            left,
+            right,
+            message,
+        } => {
+            for value in [Some(left), Some(right), message.as_ref()]
+                .into_iter()
+                .flatten()
+            {
+                expression(value, line_number, scope, lints);
+            }
+        }
+
+        Statement::BlockCall(name, arguments, span) => {
+            use_variable(name, *span, line_number, scope, lints);
+
+            for argument in arguments {
+                expression(argument, line_number, scope, lints);
+            }
+        }
+
+        Statement::Exit => return true,
+
+        Statement::Empty | Statement::Comment | Statement::Invalid => {}
+    }
+
+    false
+}
+
+// Statements of a body that all sit on the same line
+fn sequence(nodes: &[Node], line_number: i32, scope: &mut Scope, lints: &mut Vec<Lint>) {
+    let mut exited = false;
+
+    for node in nodes {
+        if matches!(node.statement, Statement::Empty) {
+            continue;
+        }
+
+        if exited {
+            lints.push(Lint {
+                rule: "unreachable-code",
+                line_number,
+                column: node.span.start,
+                end: node.span.end,
+                message: "unreachable code after `exit ()`".into(),
+            });
+            break;
+        }
+
+        exited = statement(node, line_number, scope, lints);
+    }
+}
+
+// Walks an expression, reporting what's wrong with it, and returns its datatype if it's known
+fn expression(
+    value: &Expression,
+    line_number: i32,
+    scope: &mut Scope,
+    lints: &mut Vec<Lint>,
+) -> Option<DataType> {
+    match value {
+        Expression::Literal(datatype, _, _) => Some(*datatype),
+
+        Expression::Variable(name, span) => {
+            use_variable(name, *span, line_number, scope, lints);
+            scope.defined.get(name).copied().flatten()
+        }
+
+        Expression::Arithmetic(operands, _) => {
+            for operand in operands {
+                expression(operand, line_number, scope, lints);
+            }
+
+            Some(DataType::Float)
+        }
+
+        Expression::Call(name, arguments, span) => {
+            let datatypes: Vec<Option<DataType>> = arguments
+                .iter()
+                .map(|argument| expression(argument, line_number, scope, lints))
+                .collect();
+
+            match name.as_str() {
+                "eq" | "ne" => {
+                    if let [Some(first), Some(second)] = datatypes[..] {
+                        if !comparable(first, second) {
+                            lints.push(Lint {
+                                rule: "mismatched-eq",
+                                line_number,
+                                column: span.start,
+                                end: span.end,
+                                message: format!(
+                                    "`{}` compares a {} with a {}, which is always {}",
+                                    name,
+                                    represent_datatype(first),
+                                    represent_datatype(second),
+                                    if name == "eq" { "false" } else { "true" }
+                                ),
+                            });
+                        }
+                    }
+
+                    Some(DataType::Bool)
+                }
+
+                "format" => {
+                    if let Some(argument) = arguments.first() {
+                        use_placeholders(argument, scope);
+                    }
+
+                    Some(DataType::Str)
+                }
+
+                "typeof" | "nameof" => Some(DataType::Str),
+
+                _ => None,
+            }
+        }
+    }
+}
+
+fn use_variable(
+    name: &str,
+    span: Span,
+    line_number: i32,
+    scope: &mut Scope,
+    lints: &mut Vec<Lint>,
+) {
+    scope.used.insert(name.to_string());
+
+    if !scope.is_defined(name) {
+        lints.push(Lint {
+            rule: "undefined-variable",
+            line_number,
+            column: span.start,
+            end: span.end,
+            message: format!(
+                "variable '{}' is used before it's defined{}",
+                name,
+                match suggest(name, scope.defined.keys().map(|name| name.as_str())) {
+                    Some(suggestion) => format!(", did you mean `{}`?", suggestion),
+                    None => String::new(),
+                }
+            ),
+        });
+    }
+}
+
+// `{name}` placeholders in a format string count as uses of `name`
+fn use_placeholders(value: &Expression, scope: &mut Scope) {
+    if let Expression::Literal(DataType::Str, text, _) = value {
+        for piece in text.split('{').skip(1) {
+            if let Some((name, _)) = piece.split_once('}') {
+                scope.used.insert(name.to_string());
+            }
+        }
+    }
+}
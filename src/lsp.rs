@@ -0,0 +1,490 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    checker::check_source,
+    formatter::format_source,
+    helper::{magic_variable, represent_datatype, FUNCTIONS, MAGIC_VARIABLES},
+    interpreter::{Context, COMMANDS},
+    lexer::{tokenize, TokenKind},
+    linter::{definitions, lint_source, Definition},
+};
+
+// The type of a magic variable, whose value is only known while a script runs
+fn magic_datatype(name: &str) -> Option<&'static str> {
+    magic_variable(name, &Context::default()).map(|variable| represent_datatype(variable.datatype))
+}
+
+#[test]
+fn test_magic_datatype() {
+    assert_eq!(magic_datatype("$line$"), Some("str"));
+    assert_eq!(magic_datatype("$line_number$"), Some("int"));
+    assert_eq!(magic_datatype("line"), None);
+}
+
+// `silm lsp`, a language server speaking JSON-RPC over stdin and stdout. Returns
+// whether the client asked for a shutdown before exiting
+pub fn serve(lib_paths: Vec<PathBuf>) -> io::Result<bool> {
+    let mut server = Server::new(lib_paths);
+    let stdin = io::stdin();
+    let mut reader = stdin.lock();
+    let mut stdout = io::stdout();
+
+    loop {
+        let responses = match read_message(&mut reader)? {
+            Some(Ok(message)) => server.handle(&message),
+
+            Some(Err(error)) => vec![json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": -32700, "message": error.to_string() },
+            })],
+
+            // The client went away without saying `exit`
+            None => return Ok(server.shutdown),
+        };
+
+        for response in responses {
+            let body = response.to_string();
+            write!(stdout, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+        }
+        stdout.flush()?;
+
+        if server.exited {
+            return Ok(server.shutdown);
+        }
+    }
+}
+
+fn read_message(reader: &mut impl BufRead) -> io::Result<Option<serde_json::Result<Value>>> {
+    let mut length: Option<usize> = None;
+
+    loop {
+        let mut header = String::new();
+
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim();
+
+        if header.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().ok();
+            }
+        }
+    }
+
+    let mut body = vec![0; length.unwrap_or(0)];
+    reader.read_exact(&mut body)?;
+
+    Ok(Some(serde_json::from_slice(&body)))
+}
+
+pub struct Server {
+    // Open documents by URI, with the text the editor has rather than what's saved
+    documents: HashMap<String, String>,
+    lib_paths: Vec<PathBuf>,
+    shutdown: bool,
+    exited: bool,
+}
+
+impl Server {
+    pub fn new(lib_paths: Vec<PathBuf>) -> Self {
+        Server {
+            documents: HashMap::new(),
+            lib_paths,
+            shutdown: false,
+            exited: false,
+        }
+    }
+
+    // Handles one message from the client and returns the responses and
+    // notifications to send back
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap_or("");
+        let params = &message["params"];
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+
+        let result = match method {
+            "initialize" => json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "completionProvider": {},
+                    "documentFormattingProvider": true,
+                },
+                "serverInfo": { "name": "silm", "version": crate::version::VERSION },
+            }),
+
+            "shutdown" => {
+                self.shutdown = true;
+                Value::Null
+            }
+
+            "exit" => {
+                self.exited = true;
+                return Vec::new();
+            }
+
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or("");
+                self.documents.insert(uri.to_string(), text.to_string());
+
+                return vec![self.diagnostics(uri)];
+            }
+
+            "textDocument/didChange" => {
+                // Only full syncs are asked for, so the last change is the whole document
+                if let Some(text) = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                }
+
+                return vec![self.diagnostics(uri)];
+            }
+
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+
+                return vec![notification(
+                    "textDocument/publishDiagnostics",
+                    json!({ "uri": uri, "diagnostics": [] }),
+                )];
+            }
+
+            "textDocument/hover" => self.hover(uri, &params["position"]),
+
+            "textDocument/definition" => self.definition(uri, &params["position"]),
+
+            "textDocument/completion" => self.completion(uri),
+
+            "textDocument/formatting" => self.formatting(uri),
+
+            _ => {
+                // Requests have an id and need an answer, notifications don't
+                if message.get("id").is_some() && !method.starts_with("$/") {
+                    return vec![json!({
+                        "jsonrpc": "2.0",
+                        "id": message["id"],
+                        "error": { "code": -32601, "message": format!("unknown method: {}", method) },
+                    })];
+                }
+
+                return Vec::new();
+            }
+        };
+
+        match message.get("id") {
+            Some(id) => vec![json!({ "jsonrpc": "2.0", "id": id, "result": result })],
+            None => Vec::new(),
+        }
+    }
+
+    fn source(&self, uri: &str) -> &str {
+        self.documents
+            .get(uri)
+            .map(|text| text.as_str())
+            .unwrap_or("")
+    }
+
+    // Syntax errors and unresolvable paths from `silm check`, with the lints as warnings
+    fn diagnostics(&self, uri: &str) -> Value {
+        let source = self.source(uri);
+        let mut diagnostics: Vec<Value> = Vec::new();
+
+        for diagnostic in check_source(source, &uri_to_path(uri), &self.lib_paths) {
+            diagnostics.push(json!({
+                "range": range(source, diagnostic.line_number, diagnostic.column, diagnostic.end),
+                "severity": 1,
+                "source": "silm",
+                "message": diagnostic.message,
+            }));
+        }
+
+        for lint in lint_source(source) {
+            diagnostics.push(json!({
+                "range": range(source, lint.line_number, lint.column, lint.end),
+                "severity": 2,
+                "source": "silm lint",
+                "code": lint.rule,
+                "message": lint.message,
+            }));
+        }
+
+        notification(
+            "textDocument/publishDiagnostics",
+            json!({ "uri": uri, "diagnostics": diagnostics }),
+        )
+    }
+
+    fn hover(&self, uri: &str, position: &Value) -> Value {
+        let source = self.source(uri);
+
+        let (line_number, word, start, end) = match word_at(source, position) {
+            Some(word) => word,
+            None => return Value::Null,
+        };
+
+        let contents = if let Some(datatype) = magic_datatype(&word) {
+            format!(
+                "`{}`: {}\n\nworked out by the interpreter whenever it's read",
+                word, datatype
+            )
+        } else if COMMANDS.contains(&word.as_str()) {
+            format!("`{}` command", word)
+        } else if FUNCTIONS.contains(&word.as_str()) {
+            format!("`{}` function", word)
+        } else {
+            match find_definition(source, &word, line_number) {
+                Some(definition) => format!(
+                    "`{}`: {}\n\ndefined on line {}",
+                    word,
+                    definition
+                        .datatype
+                        .map(represent_datatype)
+                        .unwrap_or("unknown type"),
+                    definition.line_number
+                ),
+
+                None => return Value::Null,
+            }
+        };
+
+        json!({
+            "contents": { "kind": "markdown", "value": contents },
+            "range": range(source, line_number, start, end),
+        })
+    }
+
+    fn definition(&self, uri: &str, position: &Value) -> Value {
+        let source = self.source(uri);
+
+        match word_at(source, position)
+            .and_then(|(line_number, word, _, _)| find_definition(source, &word, line_number))
+        {
+            Some(definition) => json!({
+                "uri": uri,
+                "range": range(
+                    source,
+                    definition.line_number,
+                    definition.span.start,
+                    definition.span.end
+                ),
+            }),
+
+            None => Value::Null,
+        }
+    }
+
+    fn completion(&self, uri: &str) -> Value {
+        let mut items: Vec<Value> = Vec::new();
+
+        // Kinds 14, 3 and 6 are keyword, function and variable
+        for command in COMMANDS {
+            items.push(json!({ "label": command, "kind": 14 }));
+        }
+
+        for function in FUNCTIONS {
+            items.push(json!({ "label": function, "kind": 3 }));
+        }
+
+        for name in MAGIC_VARIABLES {
+            items.push(json!({ "label": name, "kind": 6, "detail": magic_datatype(name) }));
+        }
+
+        let mut seen: Vec<String> = Vec::new();
+
+        for definition in definitions(self.source(uri)) {
+            if !seen.contains(&definition.name) {
+                items.push(json!({
+                    "label": definition.name,
+                    "kind": 6,
+                    "detail": definition.datatype.map(represent_datatype).unwrap_or("unknown type"),
+                }));
+                seen.push(definition.name);
+            }
+        }
+
+        Value::Array(items)
+    }
+
+    fn formatting(&self, uri: &str) -> Value {
+        let source = self.source(uri);
+        let formatted = format_source(source);
+
+        if formatted == source {
+            return json!([]);
+        }
+
+        // Replacing everything is simplest, clients clamp the end to the document
+        json!([{
+            "range": {
+                "start": { "line": 0, "character": 0 },
+                "end": { "line": source.lines().count() + 1, "character": 0 },
+            },
+            "newText": formatted,
+        }])
+    }
+}
+
+#[test]
+fn test_server() {
+    let mut server = Server::new(Vec::new());
+
+    let published = server.handle(&json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": { "textDocument": {
+            "uri": "file:///tmp/test.slm",
+            "text": "let name = \"silm\"\nprintln (nme)\nprintln (name)",
+        }},
+    }));
+    let diagnostics = &published[0]["params"]["diagnostics"];
+    assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+    assert_eq!(diagnostics[0]["code"], "undefined-variable");
+    assert_eq!(diagnostics[0]["range"]["start"]["line"], 1);
+    assert_eq!(diagnostics[0]["range"]["start"]["character"], 9);
+
+    let hover = server.handle(&json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "textDocument/hover",
+        "params": {
+            "textDocument": { "uri": "file:///tmp/test.slm" },
+            "position": { "line": 2, "character": 11 },
+        },
+    }));
+    assert_eq!(
+        hover[0]["result"]["contents"]["value"],
+        "`name`: str\n\ndefined on line 1"
+    );
+
+    let definition = server.handle(&json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "textDocument/definition",
+        "params": {
+            "textDocument": { "uri": "file:///tmp/test.slm" },
+            "position": { "line": 2, "character": 10 },
+        },
+    }));
+    assert_eq!(definition[0]["result"]["range"]["start"]["character"], 4);
+
+    let formatting = server.handle(&json!({
+        "jsonrpc": "2.0",
+        "id": 3,
+        "method": "textDocument/formatting",
+        "params": { "textDocument": { "uri": "file:///tmp/test.slm" } },
+    }));
+    assert_eq!(
+        formatting[0]["result"][0]["newText"],
+        "let name = \"silm\"\nprintln (nme)\nprintln (name)\n"
+    );
+}
+
+// The latest definition of a name up to a line, which is the one in effect there
+fn find_definition(source: &str, name: &str, line_number: i32) -> Option<Definition> {
+    definitions(source)
+        .into_iter()
+        .rev()
+        .find(|definition| definition.name == name && definition.line_number <= line_number)
+}
+
+// The word under an LSP position, with its line number and byte columns
+fn word_at(source: &str, position: &Value) -> Option<(i32, String, usize, usize)> {
+    let line_index = position["line"].as_u64()? as usize;
+    let line = source.lines().nth(line_index)?;
+    let column = byte_column(line, position["character"].as_u64()? as usize);
+
+    tokenize(line)
+        .ok()?
+        .into_iter()
+        .find(|token| {
+            token.kind == TokenKind::Word && token.column <= column && column <= token.end()
+        })
+        .map(|token| {
+            (
+                line_index as i32 + 1,
+                token.text.to_string(),
+                token.column,
+                token.end(),
+            )
+        })
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+// LSP counts lines from 0 and characters in UTF-16 code units, silm counts lines
+// from 1 and columns in bytes
+fn range(source: &str, line_number: i32, start: usize, end: usize) -> Value {
+    let line_index = (line_number - 1).max(0) as usize;
+    let line = source.lines().nth(line_index).unwrap_or("");
+
+    json!({
+        "start": { "line": line_index, "character": utf16_column(line, start) },
+        "end": { "line": line_index, "character": utf16_column(line, end) },
+    })
+}
+
+fn utf16_column(line: &str, byte_column: usize) -> usize {
+    line.char_indices()
+        .take_while(|(index, _)| *index < byte_column)
+        .map(|(_, character)| character.len_utf16())
+        .sum()
+}
+
+fn byte_column(line: &str, utf16_column: usize) -> usize {
+    let mut units = 0;
+
+    for (index, character) in line.char_indices() {
+        if units >= utf16_column {
+            return index;
+        }
+        units += character.len_utf16();
+    }
+
+    line.len()
+}
+
+// Paths are only needed to resolve relative imports, so unusual URIs are passed through
+fn uri_to_path(uri: &str) -> PathBuf {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    let bytes = path.as_bytes();
+    let mut decoded: Vec<u8> = Vec::new();
+    let mut index = 0;
+
+    while index < bytes.len() {
+        match (bytes[index], path.get(index + 1..index + 3)) {
+            (b'%', Some(hex)) if u8::from_str_radix(hex, 16).is_ok() => {
+                decoded.push(u8::from_str_radix(hex, 16).unwrap());
+                index += 3;
+            }
+
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+
+    Path::new(&String::from_utf8_lossy(&decoded).into_owned()).to_path_buf()
+}
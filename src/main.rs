@@ -1,46 +1,168 @@
 use std::{
-    env::args,
-    fs::read_to_string,
-    io::{self, Write},
-    path::PathBuf,
+    env, fs, io,
+    path::{Path, PathBuf},
     process::exit,
 };
 
-use crate::{
-    interpreter::{interpret, Variable},
+use rustyline::{error::ReadlineError, history::DefaultHistory, Editor};
+
+use silm::{
+    checker::check_files,
+    debugger::Debugger,
+    formatter::format_source,
+    helper::{
+        extract_data, get_variable, magic_variable, represent_datatype, represent_value, snippet,
+        FUNCTIONS, MAGIC_VARIABLES,
+    },
+    interpreter::{
+        interpret, interpret_file, interpret_source, Context, DataType, FrameKind, Variable,
+        Warnings, COMMANDS,
+    },
+    limits::{parse_timeout, Limits, MAX_DEPTH},
+    linter::{lint_source, RULES},
+    lsp,
+    modules::{collect_slm_files, env_lib_paths, resolve_path},
+    profiler::Profiler,
+    repl::{history_path, is_incomplete, ReplHelper},
+    sandbox::Sandbox,
+    testing::run_tests,
+    trace::Trace,
+    variables::Variables,
     version::VERSION,
 };
 
-mod commands;
-mod functions;
-mod helper;
-mod interpreter;
-mod version;
-
 fn main() {
-    let args: Vec<String> = args().collect();
-    let mut variables: Vec<Variable> = Vec::new();
+    let mut args: Vec<String> = Vec::new();
+    let mut lib_paths: Vec<PathBuf> = Vec::new();
+    let mut trace = false;
+    let mut trace_file: Option<PathBuf> = None;
+    let mut profile = false;
+    let mut warnings = Warnings::Show;
+    let mut profile_collapsed: Option<PathBuf> = None;
+    let mut sandbox = Sandbox::default();
+    let mut limits = Limits::default();
 
-    if args.len() == 1 {
-        println!("Silm {} Interpreter", VERSION);
-        println!("Enter `exit ()` to quit");
+    let mut arguments = env::args();
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "-L" | "--lib-path" => match arguments.next() {
+                Some(path) => lib_paths.push(PathBuf::from(path)),
 
-        loop {
-            print!("s>>> ");
-            io::stdout().flush().unwrap();
+                None => {
+                    println!("{} requires a directory", argument);
+                    exit(1);
+                }
+            },
 
-            let mut input = String::new();
-            io::stdin().read_line(&mut input).unwrap();
+            "--trace" => trace = true,
 
-            let trimmed_input = input.trim();
+            "--trace-file" => match arguments.next() {
+                Some(path) => {
+                    trace = true;
+                    trace_file = Some(PathBuf::from(path));
+                }
 
-            interpret(
-                trimmed_input.to_string(),
-                "<stdin>".to_string(),
-                1,
-                &mut variables,
-            );
+                None => {
+                    println!("{} requires a file", argument);
+                    exit(1);
+                }
+            },
+
+            "--profile" => profile = true,
+            "--no-fs" => sandbox.no_fs = true,
+            "--no-exit" => sandbox.no_exit = true,
+            "--no-env" => sandbox.no_env = true,
+            "--no-process" => sandbox.no_process = true,
+            "--sandbox" => sandbox = Sandbox::strict(),
+
+            "--timeout" => match arguments.next().and_then(|seconds| parse_timeout(&seconds)) {
+                Some(timeout) => limits.timeout = Some(timeout),
+
+                _ => {
+                    println!("{} requires a number of seconds", argument);
+                    exit(1);
+                }
+            },
+
+            "--max-steps" | "--max-depth" | "--max-memory" => {
+                let limit = match arguments.next().map(|limit| limit.parse::<usize>()) {
+                    Some(Ok(limit)) => limit,
+
+                    _ => {
+                        println!("{} requires a number", argument);
+                        exit(1);
+                    }
+                };
+
+                match argument.as_str() {
+                    "--max-steps" => limits.max_steps = Some(limit as u64),
+
+                    "--max-depth" if limit > MAX_DEPTH => {
+                        println!("{} can be at most {}", argument, MAX_DEPTH);
+                        exit(1);
+                    }
+
+                    "--max-depth" => limits.max_depth = Some(limit),
+                    _ => limits.max_memory = Some(limit),
+                }
+            }
+
+            "-W" | "--warnings-as-errors" => warnings = Warnings::Error,
+
+            "--quiet-warnings" => warnings = Warnings::Quiet,
+
+            "--profile-collapsed" => match arguments.next() {
+                Some(path) => {
+                    profile = true;
+                    profile_collapsed = Some(PathBuf::from(path));
+                }
+
+                None => {
+                    println!("{} requires a file", argument);
+                    exit(1);
+                }
+            },
+
+            _ => args.push(argument),
         }
+    }
+
+    // Every context appends to the trace file, so it's emptied once here
+    if let Some(path) = &trace_file {
+        if let Err(error) = fs::File::create(path) {
+            println!("ERROR[0]: {}: {error}", path.display());
+            exit(1);
+        }
+    }
+
+    // Directories given on the command line take precedence over SILM_PATH
+    lib_paths.extend(env_lib_paths());
+
+    let new_context = || Context {
+        lib_paths: lib_paths.clone(),
+        warnings,
+        sandbox,
+        limits,
+        trace: match (&trace_file, trace) {
+            (Some(path), _) => Trace::file(path).ok(),
+            (None, true) => Some(Trace::stderr()),
+            (None, false) => None,
+        },
+        ..Default::default()
+    };
+
+    let mut variables: Variables = Variables::new();
+    let mut context = new_context();
+
+    if profile {
+        context.profiler = Some(Profiler::new(profile_collapsed));
+    }
+
+    if args.len() == 1 {
+        println!("Silm {} Interpreter", VERSION);
+        println!("Enter `exit ()` or press Ctrl-D to quit, `:help` for more");
+
+        repl(&mut variables, &mut context);
     } else {
         match args[1].as_str() {
             "-H" | "--help" => {
@@ -48,14 +170,68 @@ fn main() {
                     r#"silm - experimental, line-by-line-intepreted programming language
 
 USAGE: silm [OPTIONS]
+       silm <COMMAND>
+
+COMMANDS:
+    check <files>    Report syntax and type errors without running anything
+                     (or --check)
+    debug <file>     Run a file one line at a time with breakpoints, type
+                     help at the (silm) prompt for the commands
+    fmt [--check] [paths]
+                     Rewrite .slm files in the canonical layout, or only
+                     report the ones that would change with --check
+    lint [--format json] [paths]
+                     Warn about likely mistakes in .slm files, see the
+                     rules with --rules and allow one on the next line
+                     with `# silm: allow(rule)`
+    lsp              Start a language server for editors over stdin/stdout
+    test [path]      Run every `test_*` block in the .slm files under path
 
 OPTIONS:
     -H, --help       Show this help message
     -V, --version    Show interpreter version
     -Q, --quiet      Run interactive mode with less verbosity
     -E, --execute    Execute a command directly from the terminal
+    -L, --lib-path   Add a directory to search for imported files
+    -W, --warnings-as-errors
+                     Treat warnings, like comparing an int with a float,
+                     as errors
+        --quiet-warnings
+                     Don't print warnings, which otherwise go to stderr
+        --trace      Print every line to stderr before it runs, with its
+                     file and line number, including lines of blocks
+        --trace-file <file>
+                     Write the trace to a file instead
+        --profile    Print how often each line and block ran and how long
+                     it took to stderr when the script ends
+        --profile-collapsed <file>
+                     Write the time spent in each stack of lines to a file
+                     instead, for flamegraph tools
+        --no-fs      Don't let scripts read other files with interpret,
+                     import or from
+        --no-exit    Don't let scripts end the process with exit
+        --no-env     Don't let scripts read environment variables
+        --no-process Don't let scripts start other programs
+        --sandbox    All of the --no-* options above, for running code
+                     you don't trust
+        --max-steps <n>
+                     Stop with an error after running n lines, counting
+                     loops and blocks every time they run
+        --max-depth <n>
+                     Stop with an error when blocks, evals, interprets and
+                     imports are nested more than n deep (100 by default,
+                     500 at most)
+        --max-memory <bytes>
+                     Stop with an error when all live variables, in every
+                     block call and module, take more than this many bytes
+        --timeout <seconds>
+                     Stop with an error after running this long
         <filename>   Silm source code file
 
+ENVIRONMENT:
+    SILM_PATH        Library directories to search for imported files,
+                     separated like the system's PATH
+
 This program is free software: you can redistribute it and/or modify
 it under the terms of version 3 of the GNU General Public License
 as published by the Free Software Foundation.
@@ -67,44 +243,271 @@ GNU General Public License for more details."#
                 );
             }
 
+            "check" | "--check" => {
+                if args.len() > 2 {
+                    let mut errors = 0;
+                    let paths: Vec<&Path> = args[2..].iter().map(Path::new).collect();
+
+                    for diagnostic in check_files(&paths, &lib_paths) {
+                        errors += 1;
+
+                        println!("{}", diagnostic);
+                    }
+
+                    if errors > 0 {
+                        println!(
+                            "found {} error{}",
+                            errors,
+                            if errors == 1 { "" } else { "s" }
+                        );
+                        exit(1);
+                    }
+                } else {
+                    println!("very few arguments");
+                }
+            }
+
+            "debug" => {
+                if args.len() > 2 {
+                    let filepath = PathBuf::from(&args[2]);
+                    context.debugger = Some(Debugger::new());
+
+                    if let Err(error) = interpret_file(
+                        &filepath,
+                        filepath.display().to_string(),
+                        FrameKind::File,
+                        &mut variables,
+                        &mut context,
+                    ) {
+                        println!("ERROR[0]: {error}");
+                        exit(1);
+                    }
+                } else {
+                    println!("very few arguments");
+                }
+            }
+
+            "fmt" => {
+                let check_only = args[2..].iter().any(|argument| argument == "--check");
+
+                let mut paths: Vec<PathBuf> = args[2..]
+                    .iter()
+                    .filter(|argument| *argument != "--check")
+                    .map(PathBuf::from)
+                    .collect();
+
+                if paths.is_empty() {
+                    paths.push(PathBuf::from("."));
+                }
+
+                let mut files: Vec<PathBuf> = Vec::new();
+
+                for path in paths {
+                    if path.is_dir() {
+                        if let Err(error) = collect_slm_files(&path, &mut files) {
+                            println!("ERROR[0]: {}: {error}", path.display());
+                            exit(1);
+                        }
+                    } else {
+                        files.push(path);
+                    }
+                }
+
+                let mut unformatted = 0;
+
+                for file in files {
+                    match fs::read_to_string(&file) {
+                        Ok(source) => {
+                            let formatted = format_source(&source);
+
+                            if formatted != source {
+                                unformatted += 1;
+
+                                if check_only {
+                                    println!("would reformat {}", file.display());
+                                } else if let Err(error) = fs::write(&file, formatted) {
+                                    println!("ERROR[0]: {}: {error}", file.display());
+                                    exit(1);
+                                } else {
+                                    println!("reformatted {}", file.display());
+                                }
+                            }
+                        }
+
+                        Err(error) => {
+                            println!("ERROR[0]: {}: {error}", file.display());
+                            exit(1);
+                        }
+                    }
+                }
+
+                if check_only && unformatted > 0 {
+                    exit(1);
+                }
+            }
+
+            "lint" => {
+                if args[2..].iter().any(|argument| argument == "--rules") {
+                    for (rule, description) in RULES {
+                        println!("{:<20} {}", rule, description);
+                    }
+                    return;
+                }
+
+                let mut json = false;
+                let mut paths: Vec<PathBuf> = Vec::new();
+
+                let mut arguments = args[2..].iter();
+                while let Some(argument) = arguments.next() {
+                    match argument.as_str() {
+                        "--format" => match arguments.next().map(|format| format.as_str()) {
+                            Some("json") => json = true,
+                            Some("text") => json = false,
+
+                            _ => {
+                                println!("--format requires either text or json");
+                                exit(1);
+                            }
+                        },
+
+                        _ => paths.push(PathBuf::from(argument)),
+                    }
+                }
+
+                if paths.is_empty() {
+                    paths.push(PathBuf::from("."));
+                }
+
+                let mut files: Vec<PathBuf> = Vec::new();
+
+                for path in paths {
+                    if path.is_dir() {
+                        if let Err(error) = collect_slm_files(&path, &mut files) {
+                            println!("ERROR[0]: {}: {error}", path.display());
+                            exit(1);
+                        }
+                    } else {
+                        files.push(path);
+                    }
+                }
+
+                let mut found = 0;
+                let mut reports: Vec<serde_json::Value> = Vec::new();
+
+                for file in files {
+                    match fs::read_to_string(&file) {
+                        Ok(source) => {
+                            for lint in lint_source(&source) {
+                                found += 1;
+
+                                if json {
+                                    reports.push(lint.to_json(&file.display().to_string()));
+                                } else {
+                                    println!(
+                                        "{}:{}:{}: warning[{}]: {}",
+                                        file.display(),
+                                        lint.line_number,
+                                        lint.column + 1,
+                                        lint.rule,
+                                        lint.message
+                                    );
+                                    print!(
+                                        "{}",
+                                        snippet(
+                                            source
+                                                .lines()
+                                                .nth(lint.line_number as usize - 1)
+                                                .unwrap_or(""),
+                                            lint.line_number,
+                                            lint.column,
+                                            lint.end
+                                        )
+                                    );
+                                }
+                            }
+                        }
+
+                        Err(error) => {
+                            println!("ERROR[0]: {}: {error}", file.display());
+                            exit(1);
+                        }
+                    }
+                }
+
+                if json {
+                    println!("{}", serde_json::Value::Array(reports));
+                } else if found > 0 {
+                    println!(
+                        "found {} warning{}",
+                        found,
+                        if found == 1 { "" } else { "s" }
+                    );
+                }
+
+                if found > 0 {
+                    exit(1);
+                }
+            }
+
+            "lsp" => match lsp::serve(lib_paths.clone()) {
+                Ok(true) => {}
+                Ok(false) => exit(1),
+
+                Err(error) => {
+                    eprintln!("ERROR[0]: {error}");
+                    exit(1);
+                }
+            },
+
+            "test" => {
+                let path = match args.get(2) {
+                    Some(path) => Path::new(path),
+                    None => Path::new("."),
+                };
+
+                if !run_tests(path, new_context) {
+                    exit(1);
+                }
+            }
+
             "-V" | "--version" => {
                 println!("silm interpreter version {}", VERSION);
             }
 
             "-Q" | "--quiet" => loop {
                 let mut input = String::new();
-                io::stdin().read_line(&mut input).unwrap();
 
-                let trimmed_input = input.trim();
+                match io::stdin().read_line(&mut input) {
+                    Ok(0) => break,
 
-                interpret(
-                    trimmed_input.to_string(),
-                    "<stdin>".to_string(),
-                    0,
-                    &mut variables,
-                );
+                    Ok(_) => {
+                        context.resume();
+
+                        interpret(
+                            input.trim().to_string(),
+                            "<stdin>".to_string(),
+                            0,
+                            &mut variables,
+                            &mut context,
+                        )
+                    }
+
+                    Err(error) => {
+                        println!("ERROR[0]: {error}");
+                        exit(1);
+                    }
+                }
             },
 
             "-E" | "--execute" => {
                 if args.len() > 2 {
-                    let command = args[2].clone();
-
-                    let lines: Vec<&str> = command.lines().collect();
-
-                    if lines.len() < 2 {
-                        interpret(command, "<shell>".to_string(), 1, &mut variables);
-                    } else {
-                        let mut current_line = 0;
-                        for line in lines {
-                            current_line += 1;
-                            interpret(
-                                line.to_string(),
-                                "<shell>".to_string(),
-                                current_line,
-                                &mut variables,
-                            );
-                        }
-                    }
+                    context.push_frame(FrameKind::File, "<shell>", 0);
+                    interpret_source(
+                        &args[2],
+                        "<shell>".to_string(),
+                        &mut variables,
+                        &mut context,
+                    );
                 } else {
                     println!("very few arguments");
                 }
@@ -112,27 +515,277 @@ GNU General Public License for more details."#
 
             _ => {
                 let filepath = PathBuf::from(&args[1]);
-                match read_to_string(&filepath) {
-                    Ok(source) => {
-                        let mut line_number = 0;
-                        for line in source.lines() {
-                            line_number += 1;
-
-                            interpret(
-                                line.to_string(),
-                                filepath.file_name().unwrap().to_str().unwrap().to_string(),
-                                line_number,
-                                &mut variables,
-                            );
-                        }
+
+                if let Err(error) = interpret_file(
+                    &filepath,
+                    filepath.file_name().unwrap().to_str().unwrap().to_string(),
+                    FrameKind::File,
+                    &mut variables,
+                    &mut context,
+                ) {
+                    println!("ERROR[0]: {error}");
+                    exit(1);
+                }
+            }
+        }
+    }
+
+    if let Some(profiler) = &context.profiler {
+        profiler.finish();
+    }
+}
+
+fn repl(variables: &mut Variables, context: &mut Context) {
+    let mut editor: Editor<ReplHelper, DefaultHistory> = match Editor::new() {
+        Ok(editor) => editor,
+
+        Err(error) => {
+            println!("ERROR[0]: {error}");
+            exit(1);
+        }
+    };
+
+    editor.set_helper(Some(ReplHelper::default()));
+
+    let history = history_path();
+
+    if let Some(path) = &history {
+        // There's no history yet the first time around
+        let _ = editor.load_history(path);
+    }
+
+    // Ctrl-C while a statement runs stops it rather than the whole session, while
+    // typing it's handled by the editor
+    let interrupt = context.interrupt.clone();
+
+    if let Err(error) = ctrlc::set_handler(move || interrupt.interrupt()) {
+        println!("ERROR[0]: {error}");
+    }
+
+    // Every line that was interpreted, so `:save` can write the session out as a script
+    let mut session: Vec<String> = Vec::new();
+
+    // Lines of a statement that isn't complete yet, e.g. a block spread over several lines
+    let mut statement = String::new();
+
+    loop {
+        if let Some(helper) = editor.helper_mut() {
+            helper.names = variables
+                .iter()
+                .filter(|variable| !variable.identifier.starts_with('$'))
+                .map(|variable| variable.identifier.to_string())
+                .collect();
+        }
+
+        let prompt = if statement.is_empty() {
+            "s>>> "
+        } else {
+            "s... "
+        };
+
+        match editor.readline(prompt) {
+            Ok(input) => {
+                if !statement.is_empty() {
+                    statement.push(' ');
+                }
+
+                statement.push_str(input.trim());
+
+                if is_incomplete(&statement) {
+                    continue;
+                }
+
+                let input = std::mem::take(&mut statement);
+                let trimmed_input = input.trim();
+
+                if !trimmed_input.is_empty() {
+                    let _ = editor.add_history_entry(trimmed_input);
+
+                    // Saved after every line since `exit ()` quits without coming back here
+                    if let Some(path) = &history {
+                        let _ = editor.save_history(path);
                     }
+                }
 
-                    Err(error) => {
-                        println!("ERROR[0]: {error}");
-                        exit(1);
+                // Limits apply to each input on its own, so one runaway loop doesn't
+                // stop the whole session
+                context.resume();
+
+                if trimmed_input.starts_with(':') {
+                    meta_command(trimmed_input, variables, context, &mut session);
+                } else {
+                    if !trimmed_input.is_empty() {
+                        session.push(trimmed_input.to_string());
+                    }
+
+                    if !echo_expression(trimmed_input, variables, context) {
+                        interpret(
+                            trimmed_input.to_string(),
+                            "<stdin>".to_string(),
+                            1,
+                            variables,
+                            context,
+                        );
+                    }
+                }
+            }
+
+            Err(ReadlineError::Interrupted) => statement.clear(),
+
+            Err(ReadlineError::Eof) => break,
+
+            Err(error) => {
+                println!("ERROR[0]: {error}");
+                exit(1);
+            }
+        }
+    }
+}
+
+// Prints the value of a bare expression like `1 + 2` or `x` instead of treating it
+// as an unrecognised command, returns false if `input` should be interpreted instead
+fn echo_expression(input: &str, variables: &Variables, context: &mut Context) -> bool {
+    let first_token = match input.split_whitespace().next() {
+        Some(token) => token,
+        None => return false,
+    };
+
+    if COMMANDS.contains(&first_token) || first_token.starts_with('#') {
+        return false;
+    }
+
+    let known_name = match get_variable(first_token, variables) {
+        Some(variable) => {
+            if variable.datatype == DataType::Block {
+                return false;
+            }
+
+            true
+        }
+
+        None => FUNCTIONS.contains(&first_token),
+    };
+
+    match extract_data(input, variables, context) {
+        Ok(Some(variable)) => {
+            println!("{}", represent_value(&variable));
+
+            true
+        }
+
+        Ok(None) => false,
+
+        // Something like `printn ("typo")` is better reported as an unrecognised command
+        Err(error) => {
+            if known_name || !first_token.starts_with(|character: char| character.is_alphabetic()) {
+                println!("error: {}", error);
+                true
+            } else {
+                false
+            }
+        }
+    }
+}
+
+// Interactive-only commands for looking into the session, they start with a colon
+fn meta_command(
+    input: &str,
+    variables: &mut Variables,
+    context: &mut Context,
+    session: &mut Vec<String>,
+) {
+    let (command, argument) = match input.split_once(char::is_whitespace) {
+        Some((command, argument)) => (command, argument.trim()),
+        None => (input, ""),
+    };
+
+    match command {
+        ":help" => {
+            println!(
+                r#":vars [all]     List defined variables, `all` includes the $magic$ ones
+:type <expr>    Show the datatype of an expression
+:load <file>    Interpret a file into the current session
+:save <file>    Write the lines entered so far to a script
+:reset          Forget every variable, loaded module and the session's lines
+:help           Show this help message"#
+            );
+        }
+
+        ":vars" => {
+            let show_magic = argument == "all";
+
+            // The magic ones aren't assigned anywhere, they're worked out when asked for
+            let magic: Vec<Variable> = MAGIC_VARIABLES
+                .iter()
+                .filter(|_| show_magic)
+                .filter_map(|name| magic_variable(name, context))
+                .collect();
+
+            for variable in variables.iter().chain(&magic) {
+                if show_magic || !variable.identifier.starts_with('$') {
+                    if variable.datatype == DataType::Block {
+                        println!("{}: block", variable.identifier);
+                    } else {
+                        println!(
+                            "{}: {} = {}",
+                            variable.identifier,
+                            represent_datatype(variable.datatype),
+                            variable.value
+                        );
+                    }
+                }
+            }
+        }
+
+        ":type" => match extract_data(argument, variables, context) {
+            Ok(Some(variable)) => println!("{}", represent_datatype(variable.datatype)),
+            Ok(None) => println!(":type requires an expression"),
+            Err(error) => println!("error: {}", error),
+        },
+
+        ":load" => {
+            if argument.is_empty() {
+                println!(":load requires a file");
+            } else {
+                match resolve_path(argument, context) {
+                    Ok(path) => {
+                        match interpret_file(
+                            &path,
+                            argument.to_string(),
+                            FrameKind::File,
+                            variables,
+                            context,
+                        ) {
+                            Ok(()) => session.push(format!("interpret (\"{}\")", argument)),
+                            Err(error) => println!("error: {}: {}", argument, error),
+                        }
                     }
+
+                    Err(error) => println!("error: {}", error),
                 }
             }
         }
+
+        ":save" => {
+            if argument.is_empty() {
+                println!(":save requires a file");
+            } else {
+                let mut script = session.join("\n");
+                script.push('\n');
+
+                match fs::write(argument, script) {
+                    Ok(()) => println!("saved {} lines to {}", session.len(), argument),
+                    Err(error) => println!("error: {}: {}", argument, error),
+                }
+            }
+        }
+
+        ":reset" => {
+            variables.clear();
+            session.clear();
+            context.reset();
+        }
+
+        _ => println!("unknown meta-command {}, see :help", command),
     }
 }
@@ -0,0 +1,226 @@
+use std::{
+    env,
+    fs::{canonicalize, read_dir, read_to_string},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    helper::throw_warning,
+    interpreter::{interpret_file_source, Context, FrameKind},
+    parser::{parse_source, Statement},
+    variables::Variables,
+};
+
+// Looks for `filename` next to the file that's currently being interpreted,
+// then in every library directory (`--lib-path` and `SILM_PATH`) in order
+pub fn resolve_path(filename: &str, context: &Context) -> Result<PathBuf, String> {
+    let path = Path::new(filename);
+
+    if path.is_absolute() {
+        return if path.exists() {
+            Ok(path.to_path_buf())
+        } else {
+            Err(format!("{}: no such file", filename))
+        };
+    }
+
+    let base = match context.files.last().and_then(|file| file.parent()) {
+        Some(directory) => directory.to_path_buf(),
+        None => PathBuf::new(),
+    };
+
+    let mut searched: Vec<PathBuf> = vec![base];
+    searched.extend(context.lib_paths.iter().cloned());
+
+    for directory in &searched {
+        let candidate = directory.join(path);
+
+        if candidate.is_file() {
+            return Ok(candidate);
+        }
+    }
+
+    let searched: Vec<String> = searched
+        .iter()
+        .map(|directory| {
+            if directory.as_os_str().is_empty() {
+                ".".to_string()
+            } else {
+                directory.display().to_string()
+            }
+        })
+        .collect();
+
+    Err(format!(
+        "{}: not found (searched {})",
+        filename,
+        searched.join(", ")
+    ))
+}
+
+#[test]
+fn test_resolve_path() {
+    let mut context = Context::default();
+    context.files.push(PathBuf::from("examples/main.slm"));
+
+    assert_eq!(
+        resolve_path("modules/secret.slm", &context),
+        Ok(PathBuf::from("examples/modules/secret.slm"))
+    );
+    assert!(resolve_path("secret.slm", &context).is_err());
+
+    context.lib_paths.push(PathBuf::from("examples/modules"));
+
+    assert_eq!(
+        resolve_path("secret.slm", &context),
+        Ok(PathBuf::from("examples/modules/secret.slm"))
+    );
+}
+
+// Library directories listed in the `SILM_PATH` environment variable
+pub fn env_lib_paths() -> Vec<PathBuf> {
+    match env::var_os("SILM_PATH") {
+        Some(value) => env::split_paths(&value)
+            .filter(|path| !path.as_os_str().is_empty())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+// Evaluates a file into its own namespace and returns the variables it defined.
+// Every file is only ever evaluated once, later imports get the cached namespace
+pub fn load_module(filename: &str, context: &mut Context) -> Result<Variables, String> {
+    let path = resolve_path(filename, context)
+        .and_then(|path| canonicalize(path).map_err(|error| format!("{}: {}", filename, error)))?;
+
+    if let Some(module) = context.modules.get(&path) {
+        return Ok(module.clone());
+    }
+
+    if context.importing.contains(&path) {
+        let mut chain: Vec<String> = context
+            .importing
+            .iter()
+            .skip_while(|importing| **importing != path)
+            .map(|importing| display_name(importing))
+            .collect();
+
+        chain.push(display_name(&path));
+
+        return Err(format!("circular import: {}", chain.join(" -> ")));
+    }
+
+    let source = read_to_string(&path).map_err(|error| format!("{}: {}", filename, error))?;
+
+    for (line_number, command) in side_effects(&source) {
+        throw_warning(
+            &format!(
+                "{}:{}: `{}` runs when the module is imported",
+                filename, line_number, command
+            ),
+            "import",
+            context,
+        );
+    }
+
+    let mut module_variables: Variables = Variables::new();
+
+    // The importer's variables stay alive while the module runs, and the module's own
+    // stay cached after
+    let caller_memory = (context.memory, context.scope_memory);
+    context.memory += context.scope_memory;
+
+    context.importing.push(path.clone());
+    interpret_file_source(
+        &path,
+        &source,
+        filename.to_string(),
+        FrameKind::Import,
+        &mut module_variables,
+        context,
+    );
+    context.importing.pop();
+
+    (context.memory, context.scope_memory) = caller_memory;
+    context.memory += module_variables.bytes();
+
+    context.modules.insert(path, module_variables.clone());
+
+    Ok(module_variables)
+}
+
+// Top-level lines of a module that do more than define things, which most likely
+// weren't meant to run on every import
+fn side_effects(source: &str) -> Vec<(i32, &'static str)> {
+    parse_source(source)
+        .into_iter()
+        .filter_map(|parsed_line| {
+            let command = match parsed_line.node.statement {
+                Statement::Println(_) => "println",
+                Statement::Formatln(_) => "formatln",
+                Statement::Readln { .. } => "readln",
+                Statement::Interpret(_) => "interpret",
+                Statement::Eval(_) => "eval",
+                Statement::If { .. } => "if",
+                Statement::While { .. } => "while",
+                Statement::Exit => "exit",
+                Statement::BlockCall(..) => "block call",
+                _ => return None,
+            };
+
+            Some((parsed_line.line_number, command))
+        })
+        .collect()
+}
+
+#[test]
+fn test_side_effects() {
+    assert_eq!(
+        side_effects("let x = 1\nprintln (x)\nblock b :: println (x)\nb ()\n"),
+        vec![(2, "println"), (4, "block call")]
+    );
+}
+
+// The namespace a module gets when it's imported without `as`, e.g. `secret` for "modules/secret.slm"
+pub fn module_name(filename: &str) -> Option<String> {
+    Path::new(filename)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(|stem| stem.to_string())
+}
+
+#[test]
+fn test_module_name() {
+    assert_eq!(module_name("modules/secret.slm"), Some("secret".into()));
+    assert_eq!(module_name("calculate"), Some("calculate".into()));
+    assert_eq!(module_name(""), None);
+}
+
+fn display_name(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().to_string(),
+        None => path.display().to_string(),
+    }
+}
+
+// Every .slm file under `directory`, sorted so runs are reproducible
+pub fn collect_slm_files(directory: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let mut entries: Vec<PathBuf> = read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()?;
+
+    entries.sort();
+
+    for entry in entries {
+        if entry.is_dir() {
+            collect_slm_files(&entry, files)?;
+        } else if entry
+            .extension()
+            .is_some_and(|extension| extension == "slm")
+        {
+            files.push(entry);
+        }
+    }
+
+    Ok(())
+}
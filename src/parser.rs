@@ -0,0 +1,955 @@
+use crate::{
+    helper::{parse_datatype, suggest, unknown_type, FUNCTIONS, SPACED_COLON, UNSPACED_TYPE},
+    interpreter::{DataType, COMMANDS},
+    lexer::{tokenize, SyntaxError, Token, TokenKind},
+};
+
+// Columns of a piece of a line, `end` being exclusive
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Literal(DataType, String, Span),
+    Variable(String, Span),
+    Call(String, Vec<Expression>, Span),
+    // Operands of an arithmetic expression, which always evaluates to a float
+    Arithmetic(Vec<Expression>, Span),
+}
+
+impl Expression {
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::Literal(_, _, span)
+            | Expression::Variable(_, span)
+            | Expression::Call(_, _, span)
+            | Expression::Arithmetic(_, span) => *span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Empty,
+    Comment,
+    // `const` too, which can't be assigned again
+    Let {
+        name: String,
+        name_span: Span,
+        // The datatype from `name: type`, with where the type is
+        annotation: Option<(DataType, Span)>,
+        value: Expression,
+        constant: bool,
+    },
+    Println(Option<Expression>),
+    Formatln(Expression),
+    Readln {
+        prompt: Expression,
+        target: Expression,
+    },
+    Block {
+        name: String,
+        name_span: Span,
+        parameters: Vec<(String, Span, Option<DataType>)>,
+        body: Vec<Node>,
+    },
+    Interpret(Expression),
+    Eval(Expression),
+    Import {
+        path: Expression,
+        alias: Option<(String, Span)>,
+    },
+    From {
+        path: Expression,
+        names: Vec<(String, Span)>,
+    },
+    If {
+        condition: Expression,
+        body: Vec<Node>,
+    },
+    While {
+        condition: Expression,
+        body: Vec<Node>,
+    },
+    Assert {
+        condition: Expression,
+        message: Option<Expression>,
+    },
+    AssertEq {
+        left: Expression,
+        right: Expression,
+        message: Option<Expression>,
+    },
+    Exit,
+    BlockCall(String, Vec<Expression>, Span),
+    // A statement that had a syntax error, reported separately
+    Invalid,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub statement: Statement,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedLine {
+    pub line_number: i32,
+    pub node: Node,
+    pub errors: Vec<SyntaxError>,
+}
+
+pub fn parse_source(source: &str) -> Vec<ParsedLine> {
+    let mut parsed_lines: Vec<ParsedLine> = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let mut errors: Vec<SyntaxError> = Vec::new();
+        let node = parse_statement(line, 0, &mut errors);
+
+        parsed_lines.push(ParsedLine {
+            line_number: index as i32 + 1,
+            node,
+            errors,
+        });
+    }
+
+    parsed_lines
+}
+
+// `offset` is where `text` starts in its line, so bodies of blocks report real columns
+fn parse_statement(text: &str, offset: usize, errors: &mut Vec<SyntaxError>) -> Node {
+    let start = offset + (text.len() - text.trim_start().len());
+    let span = Span {
+        start,
+        end: offset + text.trim_end().len(),
+    };
+
+    let statement = match tokenize(text) {
+        Ok(tokens) => {
+            let tokens: Vec<Token> = tokens
+                .into_iter()
+                .map(|token| Token {
+                    column: token.column + offset,
+                    ..token
+                })
+                .collect();
+
+            match statement(&tokens, text, offset, errors) {
+                Ok(statement) => statement,
+
+                Err(error) => {
+                    errors.push(error);
+                    Statement::Invalid
+                }
+            }
+        }
+
+        Err(error) => {
+            errors.push(SyntaxError::new(
+                error.column + offset,
+                error.end + offset,
+                error.message,
+            ));
+            Statement::Invalid
+        }
+    };
+
+    Node { statement, span }
+}
+
+fn statement(
+    tokens: &[Token],
+    text: &str,
+    offset: usize,
+    errors: &mut Vec<SyntaxError>,
+) -> Result<Statement, SyntaxError> {
+    let command = match tokens.first() {
+        Some(token) => token,
+        None => return Ok(Statement::Empty),
+    };
+
+    match command.kind {
+        TokenKind::Comment => return Ok(Statement::Comment),
+        TokenKind::Word => {}
+        _ => return Err(unexpected(command, "expected a command")),
+    }
+
+    if let Some(comment) = tokens.iter().find(|token| token.kind == TokenKind::Comment) {
+        return Err(SyntaxError::new(
+            comment.column,
+            comment.end(),
+            "comments must be on their own line",
+        ));
+    }
+
+    let arguments = &tokens[1..];
+
+    if let Some(first) = arguments.first() {
+        if !first.spaced {
+            return Err(SyntaxError::new(
+                command.column,
+                first.end(),
+                format!("expected a space after `{}`", command.text),
+            ));
+        }
+    }
+
+    match command.text {
+        "let" | "const" => {
+            let usage = || {
+                SyntaxError::new(
+                    command.column,
+                    end_of(tokens),
+                    format!("expected `{} <name> = <value>`", command.text),
+                )
+            };
+
+            let (name, rest) = match arguments.split_first() {
+                Some((name, rest)) => (expect_name(name)?, rest),
+                None => return Err(usage()),
+            };
+
+            // `interpret` only sees `name: type` as a name ending in a colon
+            let (annotation, rest) = match rest {
+                [colon, datatype_token, rest @ ..] if colon.kind == TokenKind::Colon => {
+                    if colon.spaced {
+                        return Err(unexpected(colon, SPACED_COLON));
+                    }
+
+                    if !datatype_token.spaced {
+                        return Err(unexpected(datatype_token, UNSPACED_TYPE));
+                    }
+
+                    (Some(datatype(datatype_token)?), rest)
+                }
+
+                _ => (None, rest),
+            };
+
+            if rest.len() < 2 {
+                return Err(usage());
+            }
+
+            if rest[0].kind != TokenKind::Assign || !rest[1].spaced {
+                return Err(unexpected(
+                    &rest[0],
+                    "expected ` = ` after the variable name",
+                ));
+            }
+
+            match expression(&rest[1..])? {
+                Some(value) => Ok(Statement::Let {
+                    name: name.0,
+                    name_span: name.1,
+                    annotation,
+                    value,
+                    constant: command.text == "const",
+                }),
+                None => Err(unexpected(&rest[0], "empty value given")),
+            }
+        }
+
+        "println" => Ok(Statement::Println(expression(parenthesised(
+            command, arguments,
+        )?)?)),
+
+        "formatln" | "interpret" | "eval" => {
+            let value = required(command, expression(parenthesised(command, arguments)?)?)?;
+
+            Ok(match command.text {
+                "formatln" => Statement::Formatln(value),
+                "interpret" => Statement::Interpret(value),
+                _ => Statement::Eval(value),
+            })
+        }
+
+        "readln" => {
+            let mut values = expressions(command, parenthesised(command, arguments)?, 2, 2)?;
+            let target = values.pop().unwrap();
+            let prompt = values.pop().unwrap();
+
+            match target {
+                Expression::Variable(_, _) => Ok(Statement::Readln { prompt, target }),
+                _ => Err(SyntaxError::new(
+                    target.span().start,
+                    target.span().end,
+                    "second argument must be a variable",
+                )),
+            }
+        }
+
+        "assert" => {
+            let mut values = expressions(command, parenthesised(command, arguments)?, 1, 2)?;
+            let message = if values.len() == 2 {
+                values.pop()
+            } else {
+                None
+            };
+
+            Ok(Statement::Assert {
+                condition: values.pop().unwrap(),
+                message,
+            })
+        }
+
+        "assert_eq" => {
+            let mut values = expressions(command, parenthesised(command, arguments)?, 2, 3)?;
+            let message = if values.len() == 3 {
+                values.pop()
+            } else {
+                None
+            };
+            let right = values.pop().unwrap();
+
+            Ok(Statement::AssertEq {
+                left: values.pop().unwrap(),
+                right,
+                message,
+            })
+        }
+
+        "import" => {
+            let (path_tokens, alias) = if arguments.len() >= 3
+                && arguments[arguments.len() - 2].kind == TokenKind::Word
+                && arguments[arguments.len() - 2].text == "as"
+            {
+                (
+                    &arguments[..arguments.len() - 2],
+                    Some(expect_name(&arguments[arguments.len() - 1])?),
+                )
+            } else {
+                (arguments, None)
+            };
+
+            let path = required(command, expression(parenthesised(command, path_tokens)?)?)?;
+
+            Ok(Statement::Import { path, alias })
+        }
+
+        "from" => {
+            let import = arguments
+                .iter()
+                .position(|token| token.kind == TokenKind::Word && token.text == "import");
+
+            match import {
+                Some(index) => {
+                    let path = required(
+                        command,
+                        expression(parenthesised(command, &arguments[..index])?)?,
+                    )?;
+
+                    let mut names: Vec<(String, Span)> = Vec::new();
+
+                    for name in split_commas(&arguments[index + 1..]) {
+                        match name {
+                            [token] => names.push(expect_name(token)?),
+                            [] => {
+                                return Err(unexpected(
+                                    &arguments[index],
+                                    "empty name in import list",
+                                ))
+                            }
+                            [_, extra, ..] => {
+                                return Err(unexpected(extra, "expected a comma between names"))
+                            }
+                        }
+                    }
+
+                    Ok(Statement::From { path, names })
+                }
+
+                None => Err(SyntaxError::new(
+                    command.column,
+                    end_of(tokens),
+                    "no `import` list provided",
+                )),
+            }
+        }
+
+        "block" => match arguments {
+            [name, rest @ ..] => {
+                let (name, name_span) = expect_name(name)?;
+
+                // Parameters come before the first `::`, the body might have more of them
+                let colons = rest
+                    .iter()
+                    .position(|token| token.kind == TokenKind::Colons)
+                    .unwrap_or(rest.len());
+
+                let parameters = if colons == 0 {
+                    Vec::new()
+                } else {
+                    parameters(command, &rest[..colons])?
+                };
+
+                match &rest[colons..] {
+                    [colons, after @ ..]
+                        if colons.spaced && after.first().is_none_or(|next| next.spaced) =>
+                    {
+                        Ok(Statement::Block {
+                            name,
+                            name_span,
+                            parameters,
+                            body: body(colons, text, offset, errors),
+                        })
+                    }
+
+                    _ => Err(SyntaxError::new(
+                        name_span.start,
+                        end_of(tokens),
+                        "expected ` :: ` after the block name",
+                    )),
+                }
+            }
+
+            [] => Err(SyntaxError::new(
+                command.column,
+                command.end(),
+                "block can not be nameless",
+            )),
+        },
+
+        "if" | "while" => match arguments
+            .iter()
+            .position(|token| token.kind == TokenKind::Colons)
+        {
+            Some(index)
+                if arguments[index].spaced
+                    && arguments.get(index + 1).is_some_and(|next| next.spaced) =>
+            {
+                let condition = required(
+                    command,
+                    expression(parenthesised(command, &arguments[..index])?)?,
+                )?;
+                let body = body(&arguments[index], text, offset, errors);
+
+                Ok(if command.text == "if" {
+                    Statement::If { condition, body }
+                } else {
+                    Statement::While { condition, body }
+                })
+            }
+
+            _ => Err(SyntaxError::new(
+                command.column,
+                end_of(tokens),
+                "no condition-bound code provided",
+            )),
+        },
+
+        "exit" => match arguments {
+            [left, right] if is_empty_call(left, right) => Ok(Statement::Exit),
+            _ => Err(SyntaxError::new(
+                command.column,
+                end_of(tokens),
+                "expected `exit ()`",
+            )),
+        },
+
+        _ => match arguments {
+            // Something like `printn (x)` is far more likely a typo than a block
+            [left, right, ..]
+                if left.kind == TokenKind::LeftParen
+                    && left.spaced
+                    && (is_empty_call(left, right)
+                        || suggest(command.text, COMMANDS.iter().copied()).is_none()) =>
+            {
+                Ok(Statement::BlockCall(
+                    command.text.to_string(),
+                    expressions(command, parenthesised(command, arguments)?, 0, usize::MAX)?,
+                    Span {
+                        start: command.column,
+                        end: command.end(),
+                    },
+                ))
+            }
+
+            _ => Err(SyntaxError::new(
+                command.column,
+                end_of(tokens),
+                match suggest(command.text, COMMANDS.iter().copied()) {
+                    Some(suggestion) => format!(
+                        "unrecognised command `{}`, did you mean `{}`?",
+                        command.text, suggestion
+                    ),
+
+                    None => format!(
+                        "unrecognised command `{}`, block calls look like `{} ()`",
+                        command.text, command.text
+                    ),
+                },
+            )),
+        },
+    }
+}
+
+#[test]
+fn test_parse_source() {
+    let parse_line = |line: &str| {
+        let parsed_line = parse_source(line).remove(0);
+        (parsed_line.node, parsed_line.errors)
+    };
+
+    let (node, errors) = parse_line("let x = 10 + y");
+
+    assert!(errors.is_empty());
+    assert!(matches!(
+        node.statement,
+        Statement::Let { ref name, value: Expression::Arithmetic(_, _), .. } if name == "x"
+    ));
+
+    let (node, errors) = parse_line("const pi = 3.14");
+
+    assert!(errors.is_empty());
+    assert!(matches!(
+        node.statement,
+        Statement::Let { constant: true, .. }
+    ));
+
+    let (node, errors) = parse_line("block greet :: let name = \"\" \\; printn (name)");
+
+    assert!(matches!(node.statement, Statement::Block { ref body, .. } if body.len() == 2));
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].column, 32);
+
+    let (node, errors) = parse_line("greet (\"a\", 1 + 2)");
+
+    assert!(errors.is_empty());
+    assert!(
+        matches!(node.statement, Statement::BlockCall(_, ref arguments, _) if arguments.len() == 2)
+    );
+
+    let (node, errors) = parse_line("block greet (name: str, times) :: println (name)");
+
+    assert!(errors.is_empty());
+    assert!(matches!(
+        node.statement,
+        Statement::Block { ref parameters, .. }
+            if parameters[0].2 == Some(DataType::Str) && parameters[1].2.is_none()
+    ));
+
+    let (_, errors) = parse_line("let x : int = 1");
+    assert_eq!(errors[0].message, "expected the `:` right after the name");
+
+    let (_, errors) = parse_line("println(\"x\")");
+    assert_eq!(errors[0].message, "expected a space after `println`");
+
+    let (_, errors) = parse_line("let y = (1 + 2) * 3");
+    assert_eq!(errors[0].column, 8);
+
+    let (_, errors) = parse_line("if (eq (x, 1, 2)) :: exit ()");
+    assert_eq!(errors[0].message, "`eq` takes 2 arguments, found 3");
+
+    let (_, errors) = parse_line("from (\"a.slm\") import x, y as z");
+    assert_eq!(errors[0].message, "expected a comma between names");
+}
+
+// Parses every statement in a block/condition body, which `interpret` splits on `\;`
+fn body(colons: &Token, text: &str, offset: usize, errors: &mut Vec<SyntaxError>) -> Vec<Node> {
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut start = colons.end() - offset;
+
+    for section in text[start..].split("\\;") {
+        nodes.push(parse_statement(section, offset + start, errors));
+        start += section.len() + 2;
+    }
+
+    nodes
+}
+
+fn expression(tokens: &[Token]) -> Result<Option<Expression>, SyntaxError> {
+    let span = match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => Span {
+            start: first.column,
+            end: last.end(),
+        },
+        _ => return Ok(None),
+    };
+
+    if let [token] = tokens {
+        return match token.kind {
+            TokenKind::Str => Ok(Some(Expression::Literal(
+                DataType::Str,
+                token.text[1..token.text.len() - 1].to_string(),
+                span,
+            ))),
+
+            TokenKind::Char => {
+                let value = &token.text[1..token.text.len() - 1];
+
+                if value.chars().count() > 1 {
+                    Err(unexpected(token, "invalid char assignment"))
+                } else {
+                    Ok(Some(Expression::Literal(
+                        DataType::Char,
+                        value.to_string(),
+                        span,
+                    )))
+                }
+            }
+
+            TokenKind::Int => match token.text.parse::<i32>() {
+                Ok(_) => Ok(Some(Expression::Literal(
+                    DataType::Int,
+                    token.text.to_string(),
+                    span,
+                ))),
+                Err(_) => Err(unexpected(token, "invalid int assignment")),
+            },
+
+            TokenKind::Float => match token.text.parse::<f64>() {
+                Ok(_) => Ok(Some(Expression::Literal(
+                    DataType::Float,
+                    token.text.to_string(),
+                    span,
+                ))),
+                Err(_) => Err(unexpected(token, "invalid float assignment")),
+            },
+
+            TokenKind::Word => {
+                if token.text == "true" || token.text == "false" {
+                    Ok(Some(Expression::Literal(
+                        DataType::Bool,
+                        token.text.to_string(),
+                        span,
+                    )))
+                } else {
+                    Ok(Some(Expression::Variable(token.text.to_string(), span)))
+                }
+            }
+
+            _ => Err(unexpected(token, "expected a value")),
+        };
+    }
+
+    let first = &tokens[0];
+
+    if first.kind == TokenKind::Word && tokens[1].kind == TokenKind::LeftParen {
+        if !tokens[1].spaced {
+            return Err(SyntaxError::new(
+                first.column,
+                tokens[1].end(),
+                format!("expected a space after `{}`", first.text),
+            ));
+        }
+
+        let arguments = parenthesised(first, &tokens[1..])?;
+
+        let mut values: Vec<Expression> = Vec::new();
+
+        for argument in split_commas(arguments) {
+            match expression(argument)? {
+                Some(value) => values.push(value),
+                None => {
+                    if !arguments.is_empty() {
+                        return Err(SyntaxError::new(
+                            span.start,
+                            span.end,
+                            "empty argument given",
+                        ));
+                    }
+                }
+            }
+        }
+
+        if let Some(arity) = function_arity(first.text) {
+            if values.len() != arity {
+                return Err(SyntaxError::new(
+                    span.start,
+                    span.end,
+                    format!(
+                        "`{}` takes {} argument{}, found {}",
+                        first.text,
+                        arity,
+                        if arity == 1 { "" } else { "s" },
+                        values.len()
+                    ),
+                ));
+            }
+        }
+
+        return Ok(Some(Expression::Call(first.text.to_string(), values, span)));
+    }
+
+    arithmetic(tokens, span).map(Some)
+}
+
+// The standard library functions with how many arguments each of them takes
+fn function_arity(name: &str) -> Option<usize> {
+    match name {
+        "eq" | "ne" => Some(2),
+        _ if FUNCTIONS.contains(&name) => Some(1),
+        _ => None,
+    }
+}
+
+// The shunting yard algorithm only understands numbers, variables, `+-*/%`
+// and parantheses, all separated by whitespace
+fn arithmetic(tokens: &[Token], span: Span) -> Result<Expression, SyntaxError> {
+    let mut operands: Vec<Expression> = Vec::new();
+    let mut expects_operand = true;
+    let mut depth = 0;
+
+    for (index, token) in tokens.iter().enumerate() {
+        if index > 0 && !token.spaced {
+            return Err(SyntaxError::new(
+                tokens[index - 1].column,
+                token.end(),
+                "arithmetic tokens must be separated by spaces",
+            ));
+        }
+
+        match token.kind {
+            TokenKind::Int | TokenKind::Float | TokenKind::Word if expects_operand => {
+                if token.text == "true" || token.text == "false" {
+                    return Err(unexpected(token, "only numbers can be used in arithmetic"));
+                }
+
+                operands.push(expression(std::slice::from_ref(token))?.unwrap());
+                expects_operand = false;
+            }
+
+            TokenKind::Operator if !expects_operand => {
+                if token.text == "^" {
+                    return Err(unexpected(token, "unsupported operator `^`"));
+                }
+
+                expects_operand = true;
+            }
+
+            TokenKind::LeftParen if expects_operand => depth += 1,
+
+            TokenKind::RightParen if !expects_operand && depth > 0 => depth -= 1,
+
+            TokenKind::Str | TokenKind::Char => {
+                return Err(unexpected(token, "only numbers can be used in arithmetic"))
+            }
+
+            _ => {
+                return Err(unexpected(
+                    token,
+                    if expects_operand {
+                        "expected a value"
+                    } else {
+                        "expected an operator"
+                    },
+                ))
+            }
+        }
+    }
+
+    if expects_operand || depth != 0 {
+        return Err(SyntaxError::new(
+            span.start,
+            span.end,
+            "incomplete expression",
+        ));
+    }
+
+    Ok(Expression::Arithmetic(operands, span))
+}
+
+// What's inside the parantheses that must wrap the rest of a command or function call
+fn parenthesised<'a, 'b>(
+    command: &Token,
+    tokens: &'b [Token<'a>],
+) -> Result<&'b [Token<'a>], SyntaxError> {
+    let missing = || {
+        SyntaxError::new(
+            command.column,
+            end_of(tokens).max(command.end()),
+            format!("`{}` call does not contain two parantheses", command.text),
+        )
+    };
+
+    match tokens.first() {
+        Some(first) if first.kind == TokenKind::LeftParen => {
+            let mut depth = 0;
+
+            for (index, token) in tokens.iter().enumerate() {
+                match token.kind {
+                    TokenKind::LeftParen => depth += 1,
+                    TokenKind::RightParen => depth -= 1,
+                    _ => {}
+                }
+
+                if depth == 0 {
+                    return if index == tokens.len() - 1 {
+                        Ok(&tokens[1..index])
+                    } else {
+                        Err(unexpected(
+                            &tokens[index + 1],
+                            "unexpected tokens after the parantheses",
+                        ))
+                    };
+                }
+            }
+
+            Err(SyntaxError::new(
+                first.column,
+                end_of(tokens),
+                "unclosed paranthesis",
+            ))
+        }
+
+        _ => Err(missing()),
+    }
+}
+
+// Comma separated values, between `min` and `max` of them
+fn expressions(
+    command: &Token,
+    tokens: &[Token],
+    min: usize,
+    max: usize,
+) -> Result<Vec<Expression>, SyntaxError> {
+    let mut values: Vec<Expression> = Vec::new();
+
+    for argument in split_commas(tokens) {
+        values.push(required(command, expression(argument)?)?);
+    }
+
+    if values.len() < min || values.len() > max {
+        return Err(SyntaxError::new(
+            command.column,
+            end_of(tokens).max(command.end()),
+            if min == max {
+                format!("`{}` takes {} arguments", command.text, min)
+            } else {
+                format!("`{}` takes {} to {} arguments", command.text, min, max)
+            },
+        ));
+    }
+
+    Ok(values)
+}
+
+fn split_commas<'a, 'b>(tokens: &'b [Token<'a>]) -> Vec<&'b [Token<'a>]> {
+    let mut sections: Vec<&[Token]> = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::LeftParen => depth += 1,
+            TokenKind::RightParen => depth -= 1,
+            TokenKind::Comma if depth == 0 => {
+                sections.push(&tokens[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if start < tokens.len() || !sections.is_empty() {
+        sections.push(&tokens[start..]);
+    }
+
+    sections
+}
+
+// `(a: int, b)` after a block's name
+fn parameters(
+    command: &Token,
+    tokens: &[Token],
+) -> Result<Vec<(String, Span, Option<DataType>)>, SyntaxError> {
+    let mut parameters: Vec<(String, Span, Option<DataType>)> = Vec::new();
+
+    if tokens[0].kind != TokenKind::LeftParen {
+        return Err(SyntaxError::new(
+            tokens[0].column,
+            end_of(tokens),
+            "parameters must be inside parantheses",
+        ));
+    }
+
+    for parameter in split_commas(parenthesised(command, tokens)?) {
+        let (name, span, datatype) = match parameter {
+            [name] => {
+                let (name, span) = expect_name(name)?;
+                (name, span, None)
+            }
+
+            [name, colon, datatype_token] if colon.kind == TokenKind::Colon => {
+                let (name, span) = expect_name(name)?;
+                (name, span, Some(datatype(datatype_token)?.0))
+            }
+
+            [] => return Err(unexpected(&tokens[0], "empty parameter")),
+
+            [_, extra, ..] => return Err(unexpected(extra, "expected `<name>: <type>`")),
+        };
+
+        if parameters.iter().any(|(existing, _, _)| *existing == name) {
+            return Err(SyntaxError::new(
+                span.start,
+                span.end,
+                format!("parameter '{}' is given twice", name),
+            ));
+        }
+
+        parameters.push((name, span, datatype));
+    }
+
+    Ok(parameters)
+}
+
+fn datatype(token: &Token) -> Result<(DataType, Span), SyntaxError> {
+    let span = Span {
+        start: token.column,
+        end: token.end(),
+    };
+
+    match parse_datatype(token.text) {
+        Some(datatype) if token.kind == TokenKind::Word => Ok((datatype, span)),
+        _ => Err(SyntaxError::new(
+            span.start,
+            span.end,
+            unknown_type(token.text),
+        )),
+    }
+}
+
+fn required(command: &Token, value: Option<Expression>) -> Result<Expression, SyntaxError> {
+    match value {
+        Some(value) => Ok(value),
+        None => Err(SyntaxError::new(
+            command.column,
+            command.end(),
+            "missing argument",
+        )),
+    }
+}
+
+fn expect_name(token: &Token) -> Result<(String, Span), SyntaxError> {
+    if token.kind == TokenKind::Word {
+        Ok((
+            token.text.to_string(),
+            Span {
+                start: token.column,
+                end: token.end(),
+            },
+        ))
+    } else {
+        Err(unexpected(token, "expected a name"))
+    }
+}
+
+// `()` written exactly like that, as `exit` and block calls expect
+fn is_empty_call(left: &Token, right: &Token) -> bool {
+    left.kind == TokenKind::LeftParen
+        && left.spaced
+        && right.kind == TokenKind::RightParen
+        && !right.spaced
+}
+
+fn unexpected(token: &Token, message: &str) -> SyntaxError {
+    SyntaxError::new(token.column, token.end(), message)
+}
+
+fn end_of(tokens: &[Token]) -> usize {
+    tokens.last().map(|token| token.end()).unwrap_or(0)
+}
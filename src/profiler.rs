@@ -0,0 +1,181 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crate::interpreter::Frame;
+
+#[derive(Debug, Default, Clone)]
+struct Timing {
+    count: u64,
+    total: Duration,
+    // The line's code, kept to make the report readable
+    text: String,
+}
+
+// `--profile`, which times every line of a file or block and every block call
+#[derive(Debug, Default)]
+pub struct Profiler {
+    lines: HashMap<(String, i32), Timing>,
+    blocks: HashMap<String, Timing>,
+    // Time spent in each line itself, minus the lines it ran, keyed by the frames
+    // that led to it, for flamegraph tools
+    stacks: HashMap<String, Duration>,
+    // For every line that's running, when it started and how long the lines it ran took
+    running: Vec<(Instant, Duration)>,
+    collapsed: Option<PathBuf>,
+}
+
+impl Profiler {
+    pub fn new(collapsed: Option<PathBuf>) -> Self {
+        Profiler {
+            collapsed,
+            ..Default::default()
+        }
+    }
+
+    pub fn start_line(&mut self) {
+        self.running.push((Instant::now(), Duration::ZERO));
+    }
+
+    pub fn end_line(&mut self, line: &str, input_name: &str, line_number: i32, frames: &[Frame]) {
+        let (started, nested) = match self.running.pop() {
+            Some(running) => running,
+            None => return,
+        };
+
+        let elapsed = started.elapsed();
+
+        if let Some((_, parent_nested)) = self.running.last_mut() {
+            *parent_nested += elapsed;
+        }
+
+        if line.trim().is_empty() || line.trim().starts_with('#') {
+            return;
+        }
+
+        let timing = self
+            .lines
+            .entry((input_name.to_string(), line_number))
+            .or_insert_with(|| Timing {
+                text: line.trim().to_string(),
+                ..Default::default()
+            });
+
+        timing.count += 1;
+        timing.total += elapsed;
+
+        let stack = if frames.is_empty() {
+            format!("{}:{}", input_name, line_number)
+        } else {
+            frames
+                .iter()
+                .map(|frame| format!("{}:{}", frame.input_name, frame.line_number))
+                .collect::<Vec<String>>()
+                .join(";")
+        };
+
+        *self.stacks.entry(stack).or_default() += elapsed.saturating_sub(nested);
+    }
+
+    pub fn block(&mut self, name: &str, elapsed: Duration) {
+        let timing = self.blocks.entry(name.to_string()).or_default();
+
+        timing.count += 1;
+        timing.total += elapsed;
+    }
+
+    // Prints the report to stderr, slowest first, or writes the collapsed stacks
+    pub fn finish(&self) {
+        if let Some(path) = &self.collapsed {
+            let mut stacks: Vec<(&String, &Duration)> = self.stacks.iter().collect();
+            stacks.sort();
+
+            let collapsed: String = stacks
+                .iter()
+                .filter(|(_, time)| time.as_micros() > 0)
+                .map(|(stack, time)| format!("{} {}\n", stack, time.as_micros()))
+                .collect();
+
+            if let Err(error) = fs::write(path, collapsed) {
+                eprintln!("ERROR[0]: {}: {error}", path.display());
+            }
+
+            return;
+        }
+
+        let mut lines: Vec<(&(String, i32), &Timing)> = self.lines.iter().collect();
+        lines.sort_by(|a, b| b.1.total.cmp(&a.1.total).then(a.0.cmp(b.0)));
+
+        eprintln!("{:>8} {:>12}  line", "count", "total");
+
+        for ((input_name, line_number), timing) in lines {
+            eprintln!(
+                "{:>8} {:>12}  {}:{}  {}",
+                timing.count,
+                format!("{:.3?}", timing.total),
+                input_name,
+                line_number,
+                timing.text
+            );
+        }
+
+        if !self.blocks.is_empty() {
+            let mut blocks: Vec<(&String, &Timing)> = self.blocks.iter().collect();
+            blocks.sort_by(|a, b| b.1.total.cmp(&a.1.total).then(a.0.cmp(b.0)));
+
+            eprintln!();
+            eprintln!("{:>8} {:>12}  block", "count", "total");
+
+            for (name, timing) in blocks {
+                eprintln!(
+                    "{:>8} {:>12}  {}",
+                    timing.count,
+                    format!("{:.3?}", timing.total),
+                    name
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_end_line() {
+    use crate::interpreter::FrameKind;
+
+    let frames = vec![
+        Frame {
+            kind: FrameKind::File,
+            input_name: "main.slm".into(),
+            line_number: 2,
+        },
+        Frame {
+            kind: FrameKind::Block,
+            input_name: "<block b>".into(),
+            line_number: 1,
+        },
+    ];
+
+    let mut profiler = Profiler::new(None);
+
+    profiler.start_line();
+    profiler.start_line();
+    std::thread::sleep(Duration::from_millis(2));
+    profiler.end_line("println (1)", "<block b>", 1, &frames);
+    profiler.end_line("b ()", "main.slm", 2, &frames[..1]);
+
+    profiler.start_line();
+    profiler.end_line("", "main.slm", 3, &frames[..1]);
+
+    assert_eq!(profiler.lines.len(), 2);
+
+    let inner = &profiler.lines[&("<block b>".to_string(), 1)];
+    let outer = &profiler.lines[&("main.slm".to_string(), 2)];
+
+    // The calling line's time includes the block's, but its own share doesn't
+    assert!(outer.total >= inner.total);
+    assert!(profiler.stacks["main.slm:2;<block b>:1"] >= Duration::from_millis(2));
+    assert!(profiler.stacks["main.slm:2"] < inner.total);
+}
@@ -0,0 +1,129 @@
+use std::{env, path::PathBuf};
+
+use rustyline::{
+    completion::{Completer, Pair},
+    highlight::Highlighter,
+    hint::Hinter,
+    validate::Validator,
+    Helper,
+};
+
+use crate::{helper::FUNCTIONS, interpreter::COMMANDS};
+
+// Completes commands, standard library functions and the names of defined variables
+#[derive(Default)]
+pub struct ReplHelper {
+    pub names: Vec<String>,
+}
+
+impl ReplHelper {
+    fn candidates(&self, prefix: &str) -> Vec<Pair> {
+        let mut candidates: Vec<&str> = COMMANDS
+            .iter()
+            .chain(FUNCTIONS.iter())
+            .copied()
+            .chain(self.names.iter().map(|name| name.as_str()))
+            .filter(|candidate| candidate.starts_with(prefix))
+            .collect();
+
+        candidates.sort();
+        candidates.dedup();
+
+        candidates
+            .into_iter()
+            .map(|candidate| Pair {
+                display: candidate.to_string(),
+                replacement: candidate.to_string(),
+            })
+            .collect()
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let start = word_start(line, pos);
+
+        Ok((start, self.candidates(&line[start..pos])))
+    }
+}
+
+// Where the word under the cursor begins, words being split by spaces, parantheses and commas
+fn word_start(line: &str, pos: usize) -> usize {
+    line[..pos]
+        .rfind(|character: char| character.is_whitespace() || "(),".contains(character))
+        .map(|index| index + 1)
+        .unwrap_or(0)
+}
+
+#[test]
+fn test_word_start() {
+    assert_eq!(word_start("pri", 3), 0);
+    assert_eq!(word_start("println (typ", 12), 9);
+    assert_eq!(word_start("eq (x, nam", 10), 7);
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+// Whether the statement needs more lines: a paranthesis or string left open,
+// or a block/condition body that ends with `::` or `\;`
+pub fn is_incomplete(statement: &str) -> bool {
+    if statement.trim_start().starts_with('#') {
+        return false;
+    }
+
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+
+    for character in statement.chars() {
+        match quote {
+            Some(opening) => {
+                if character == opening {
+                    quote = None;
+                }
+            }
+
+            None => match character {
+                '"' | '\'' => quote = Some(character),
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            },
+        }
+    }
+
+    let trimmed = statement.trim_end();
+
+    quote.is_some() || depth > 0 || trimmed.ends_with("::") || trimmed.ends_with("\\;")
+}
+
+#[test]
+fn test_is_incomplete() {
+    assert!(is_incomplete("println (\"Hello"));
+    assert!(is_incomplete("println (eq (x,"));
+    assert!(is_incomplete("block greet ::"));
+    assert!(is_incomplete("while (true) :: println (x) \\;"));
+    assert!(!is_incomplete("println (\"(:: \\;\")"));
+    assert!(!is_incomplete("greet ()"));
+    assert!(!is_incomplete("# it's a comment ::"));
+    assert!(!is_incomplete(""));
+}
+
+// Interactive history is kept in the user's home directory, if there is one
+pub fn history_path() -> Option<PathBuf> {
+    env::home_dir().map(|home| home.join(".silm_history"))
+}
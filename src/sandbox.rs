@@ -0,0 +1,125 @@
+use std::fmt;
+
+use crate::host::Host;
+
+// Something a script can do to the world outside of it, which a sandbox can take away
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Capability {
+    // Reading other files with `interpret`, `import` and `from`
+    Fs,
+    // Ending the whole process with `exit`
+    Exit,
+    // Reading environment variables
+    Env,
+    // Starting other programs
+    Process,
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(match self {
+            Capability::Fs => "filesystem access",
+            Capability::Exit => "exit",
+            Capability::Env => "environment access",
+            Capability::Process => "starting processes",
+        })
+    }
+}
+
+impl Capability {
+    // The command line flag that takes it away
+    pub fn flag(&self) -> &'static str {
+        match self {
+            Capability::Fs => "--no-fs",
+            Capability::Exit => "--no-exit",
+            Capability::Env => "--no-env",
+            Capability::Process => "--no-process",
+        }
+    }
+}
+
+// What scripts aren't allowed to do, everything is allowed by default. Nothing built
+// in reads the environment or starts processes, those are for host functions that
+// declare them with `Host::require`
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Sandbox {
+    pub no_fs: bool,
+    pub no_exit: bool,
+    pub no_env: bool,
+    pub no_process: bool,
+}
+
+impl Sandbox {
+    // For running code nobody has looked at
+    pub fn strict() -> Self {
+        Sandbox {
+            no_fs: true,
+            no_exit: true,
+            no_env: true,
+            no_process: true,
+        }
+    }
+
+    pub fn allows(&self, capability: Capability) -> bool {
+        !match capability {
+            Capability::Fs => self.no_fs,
+            Capability::Exit => self.no_exit,
+            Capability::Env => self.no_env,
+            Capability::Process => self.no_process,
+        }
+    }
+
+    pub fn check(&self, capability: Capability) -> Result<(), String> {
+        if self.allows(capability) {
+            Ok(())
+        } else {
+            Err(format!(
+                "permission denied: {} is disabled ({})",
+                capability,
+                capability.flag()
+            ))
+        }
+    }
+
+    // Whether a command, or a function registered by the host, is allowed to run
+    pub fn check_name(&self, name: &str, host: &Host) -> Result<(), String> {
+        match required(name).or_else(|| host.required(name)) {
+            Some(capability) => self.check(capability),
+            None => Ok(()),
+        }
+    }
+}
+
+// What the built-in commands need
+fn required(command: &str) -> Option<Capability> {
+    match command {
+        "interpret" | "import" | "from" => Some(Capability::Fs),
+        "exit" => Some(Capability::Exit),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_check_name() {
+    let mut host = Host::default();
+    host.function("home", |(): ()| Ok(String::from("/home")));
+    host.require("home", Capability::Env);
+
+    let sandbox = Sandbox {
+        no_fs: true,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        sandbox.check_name("import", &host),
+        Err("permission denied: filesystem access is disabled (--no-fs)".to_string())
+    );
+    assert_eq!(sandbox.check_name("exit", &host), Ok(()));
+    assert_eq!(sandbox.check_name("home", &host), Ok(()));
+
+    assert_eq!(
+        Sandbox::strict().check_name("home", &host),
+        Err("permission denied: environment access is disabled (--no-env)".to_string())
+    );
+    assert_eq!(Sandbox::strict().check_name("println", &host), Ok(()));
+}
@@ -0,0 +1,109 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    fmt,
+    io::{self, BufRead, Write},
+    rc::Rc,
+};
+
+// Where `println`, `readln`, errors and warnings go, so silm can run somewhere
+// other than a terminal
+pub trait Io {
+    fn stdout(&mut self) -> &mut dyn Write;
+    fn stderr(&mut self) -> &mut dyn Write;
+    // A line without its newline, or `None` once there's nothing left to read
+    fn read_line(&mut self) -> io::Result<Option<String>>;
+}
+
+impl fmt::Debug for dyn Io {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Io")
+    }
+}
+
+// The process's own stdin, stdout and stderr
+pub struct Terminal {
+    stdout: io::Stdout,
+    stderr: io::Stderr,
+}
+
+impl Default for Terminal {
+    fn default() -> Self {
+        Terminal {
+            stdout: io::stdout(),
+            stderr: io::stderr(),
+        }
+    }
+}
+
+impl Io for Terminal {
+    fn stdout(&mut self) -> &mut dyn Write {
+        &mut self.stdout
+    }
+
+    fn stderr(&mut self) -> &mut dyn Write {
+        &mut self.stderr
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+
+        match io::stdin().lock().read_line(&mut line)? {
+            0 => Ok(None),
+            _ => Ok(Some(line.trim_end_matches(['\n', '\r']).to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Buffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buffer)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Input given up front and output kept in memory, for tests and embedders. Clones
+// share their output, so one can be handed to a `Context` and the other read after
+#[derive(Debug, Clone, Default)]
+pub struct MemoryIo {
+    input: Rc<RefCell<VecDeque<String>>>,
+    stdout: Buffer,
+    stderr: Buffer,
+}
+
+impl MemoryIo {
+    pub fn new(input: &str) -> Self {
+        MemoryIo {
+            input: Rc::new(RefCell::new(input.lines().map(String::from).collect())),
+            ..Default::default()
+        }
+    }
+
+    pub fn stdout_text(&self) -> String {
+        String::from_utf8_lossy(&self.stdout.0.borrow()).into_owned()
+    }
+
+    pub fn stderr_text(&self) -> String {
+        String::from_utf8_lossy(&self.stderr.0.borrow()).into_owned()
+    }
+}
+
+impl Io for MemoryIo {
+    fn stdout(&mut self) -> &mut dyn Write {
+        &mut self.stdout
+    }
+
+    fn stderr(&mut self) -> &mut dyn Write {
+        &mut self.stderr
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        Ok(self.input.borrow_mut().pop_front())
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    interpreter::{call_block, interpret_source, Context, DataType, FrameKind, Variable},
+    modules::collect_slm_files,
+    variables::Variables,
+};
+
+// Runs every `test_*` block found in the .slm files under `path` and
+// returns whether all of them passed, `new_context` sets up each run
+pub fn run_tests(path: &Path, new_context: impl Fn() -> Context) -> bool {
+    let mut files: Vec<PathBuf> = Vec::new();
+
+    if path.is_dir() {
+        if let Err(error) = collect_slm_files(path, &mut files) {
+            println!("ERROR[0]: {error}");
+            return false;
+        }
+    } else {
+        files.push(path.to_path_buf());
+    }
+
+    let mut passed = 0;
+    let mut failed: Vec<String> = Vec::new();
+
+    for file in files {
+        let file_name = file.display().to_string();
+
+        let mut variables: Variables = Variables::new();
+        let mut context = new_context();
+
+        let source = match read_to_string(&file) {
+            Ok(source) => source,
+
+            Err(error) => {
+                println!("ERROR[0]: {}: {error}", file_name);
+                failed.push(file_name);
+                continue;
+            }
+        };
+
+        context.files.push(file.clone());
+        context.push_frame(FrameKind::File, &file_name, 0);
+        interpret_source(
+            &test_blocks(&source),
+            file_name.clone(),
+            &mut variables,
+            &mut context,
+        );
+
+        if context.errors > 0 {
+            println!("test {} ... FAILED (errors while loading)", file_name);
+            failed.push(file_name);
+            continue;
+        }
+
+        let tests: Vec<&Variable> = variables
+            .iter()
+            .filter(|variable| {
+                variable.datatype == DataType::Block && variable.identifier.starts_with("test_")
+            })
+            .collect();
+
+        for test in tests {
+            let name = format!("{}::{}", file_name, test.identifier);
+
+            // A fresh context per test so a failure can't leak into the next one
+            let mut test_context = new_context();
+
+            // An `exit` would end the whole run, so it fails the test instead
+            test_context.sandbox.no_exit = true;
+
+            // Keeps relative imports inside the test pointing next to its file
+            test_context.files.push(file.clone());
+            let called = call_block(test, Vec::new(), &mut test_context);
+
+            if let Err(error) = called {
+                // Tests can't be given arguments
+                println!("test {} ... FAILED ({})", name, error);
+                failed.push(name);
+            } else if test_context.errors == 0 {
+                println!("test {} ... ok", name);
+                passed += 1;
+            } else {
+                println!("test {} ... FAILED", name);
+                failed.push(name);
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        println!();
+        println!("failures:");
+
+        for name in &failed {
+            println!("    {}", name);
+        }
+    }
+
+    println!();
+    println!(
+        "test result: {}. {} passed; {} failed",
+        if failed.is_empty() { "ok" } else { "FAILED" },
+        passed,
+        failed.len()
+    );
+
+    failed.is_empty()
+}
+
+// Only the lines defining `test_*` blocks, with the rest blanked out so line numbers
+// stay the same, since the file's other lines could `exit` or wait on `readln` and
+// nothing they define is seen by the tests anyway
+fn test_blocks(source: &str) -> String {
+    source
+        .lines()
+        .map(|line| {
+            let mut tokens = line.split_whitespace();
+
+            match (tokens.next(), tokens.next()) {
+                (Some("block"), Some(name)) if name.starts_with("test_") => line,
+                _ => "",
+            }
+        })
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+#[test]
+fn test_run_tests() {
+    use crate::stdio::MemoryIo;
+    use std::{env::temp_dir, fs};
+
+    let directory = temp_dir().join(format!("silm_run_tests_{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+
+    // Neither the `readln` nor the `exit` ever runs
+    let passing = directory.join("passing.slm");
+    fs::write(
+        &passing,
+        "let name = \"\"\nreadln (\"name? \", name)\nexit ()\nblock test_sum :: assert_eq (1 + 1, 2.0)",
+    )
+    .unwrap();
+
+    let failing = directory.join("failing.slm");
+    fs::write(&failing, "block test_wrong :: assert (eq (1, 2))").unwrap();
+
+    let new_context = || Context {
+        io: Box::new(MemoryIo::new("")),
+        ..Default::default()
+    };
+
+    assert!(run_tests(&passing, new_context));
+    assert!(!run_tests(&failing, new_context));
+    assert!(!run_tests(&directory, new_context));
+
+    // The test calling `exit` fails and the one after it still runs
+    let exiting = directory.join("exiting.slm");
+    fs::write(
+        &exiting,
+        "block test_exit :: exit ()\nblock test_after :: println (\"after\")",
+    )
+    .unwrap();
+
+    let io = MemoryIo::new("");
+    let shared_context = || Context {
+        io: Box::new(io.clone()),
+        ..Default::default()
+    };
+
+    assert!(!run_tests(&exiting, shared_context));
+    assert!(io.stdout_text().contains("after"));
+    assert!((io.stdout_text() + &io.stderr_text()).contains("exit is disabled"));
+
+    fs::remove_dir_all(&directory).unwrap();
+}
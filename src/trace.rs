@@ -0,0 +1,86 @@
+use std::{
+    fmt,
+    fs::OpenOptions,
+    io::{self, LineWriter, Write},
+    path::Path,
+};
+
+// Where `--trace` writes every line before it runs
+pub struct Trace {
+    output: Box<dyn Write>,
+}
+
+impl fmt::Debug for Trace {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("Trace")
+    }
+}
+
+impl Trace {
+    pub fn stderr() -> Self {
+        Trace {
+            output: Box::new(io::stderr()),
+        }
+    }
+
+    // Appends line by line, since `exit ()` ends the process without flushing anything
+    pub fn file(path: &Path) -> io::Result<Self> {
+        Ok(Trace {
+            output: Box::new(LineWriter::new(
+                OpenOptions::new().create(true).append(true).open(path)?,
+            )),
+        })
+    }
+
+    // Lines run by blocks, `interpret` and `eval` are indented under their caller
+    pub fn line(&mut self, line: &str, input_name: &str, line_number: i32, depth: usize) {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            return;
+        }
+
+        // A trace that can't be written shouldn't stop the script
+        let _ = writeln!(
+            self.output,
+            "{}{}:{}: {}",
+            "  ".repeat(depth.saturating_sub(1)),
+            input_name,
+            line_number,
+            line
+        );
+    }
+}
+
+#[test]
+fn test_line() {
+    use std::{cell::RefCell, rc::Rc};
+
+    #[derive(Clone)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buffer)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let output = Shared(Rc::new(RefCell::new(Vec::new())));
+    let mut trace = Trace {
+        output: Box::new(output.clone()),
+    };
+
+    trace.line("let x = 1", "main.slm", 1, 1);
+    trace.line("", "main.slm", 2, 1);
+    trace.line("# nothing", "main.slm", 3, 1);
+    trace.line(" println (x)", "<block show>", 1, 2);
+
+    assert_eq!(
+        String::from_utf8(output.0.borrow().clone()).unwrap(),
+        "main.slm:1: let x = 1\n  <block show>:1: println (x)\n"
+    );
+}
@@ -0,0 +1,295 @@
+use std::{
+    borrow::Borrow,
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    rc::Rc,
+};
+
+use crate::interpreter::{DataType, Variable};
+
+thread_local! {
+    // Every identifier in use, and how many were left the last time the ones nothing
+    // else holds anymore were let go of
+    static NAMES: RefCell<(HashSet<Rc<str>>, usize)> = RefCell::new((HashSet::new(), 0));
+}
+
+// Names aren't let go of one by one as they're dropped, only once there are twice as
+// many as were left last time, so it doesn't cost more than interning them did
+const MIN_NAMES_KEPT: usize = 1024;
+
+// An interned identifier, so the same name in every scope, module and block shares one
+// allocation and copying it around is only a reference count
+#[derive(Clone, Eq)]
+pub struct Name(Rc<str>);
+
+impl Name {
+    pub fn new(name: &str) -> Self {
+        NAMES.with(|names| {
+            let (names, kept) = &mut *names.borrow_mut();
+
+            match names.get(name) {
+                Some(interned) => Name(interned.clone()),
+
+                None => {
+                    if names.len() >= (*kept * 2).max(MIN_NAMES_KEPT) {
+                        names.retain(|name| Rc::strong_count(name) > 1);
+                        *kept = names.len();
+                    }
+
+                    let interned: Rc<str> = Rc::from(name);
+                    names.insert(interned.clone());
+                    Name(interned)
+                }
+            }
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Name {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Hash for Name {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Name) -> bool {
+        // Interned names are only equal if they're the same allocation
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl PartialEq<str> for Name {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Name {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Name {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl From<&str> for Name {
+    fn from(name: &str) -> Self {
+        Name::new(name)
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Name::new(&name)
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&self.0)
+    }
+}
+
+impl fmt::Debug for Name {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, formatter)
+    }
+}
+
+// The variables of one scope, found by name without going through all of them and
+// kept in the order they were first assigned
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Variables {
+    slots: Vec<Variable>,
+    indices: HashMap<Name, usize>,
+    // Names bound with `const`, which `helper::assign` refuses to change
+    constants: HashSet<Name>,
+    // Datatypes given with `let name: type`, which `helper::assign` holds values to
+    annotations: HashMap<Name, DataType>,
+    // Bytes taken by the names and values, kept up to date so `--max-memory` doesn't
+    // have to add them all up after every line
+    bytes: usize,
+}
+
+impl Variables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, identifier: &str) -> Option<&Variable> {
+        self.indices
+            .get(identifier)
+            .map(|&index| &self.slots[index])
+    }
+
+    pub fn contains(&self, identifier: &str) -> bool {
+        self.indices.contains_key(identifier)
+    }
+
+    // Replaces the value and datatype of a variable with the same name, or adds it
+    pub fn assign(&mut self, variable: Variable) {
+        match self.indices.get(&*variable.identifier) {
+            Some(&index) => {
+                let existing_variable = &mut self.slots[index];
+
+                self.bytes = self.bytes - existing_variable.value.len() + variable.value.len();
+                existing_variable.value = variable.value;
+                existing_variable.datatype = variable.datatype;
+            }
+
+            None => {
+                self.bytes += variable.identifier.len() + variable.value.len();
+                self.indices
+                    .insert(variable.identifier.clone(), self.slots.len());
+                self.slots.push(variable);
+            }
+        }
+    }
+
+    // Assigns like `assign` and marks the name as constant from then on
+    pub fn assign_constant(&mut self, variable: Variable) {
+        self.constants.insert(variable.identifier.clone());
+        self.assign(variable);
+    }
+
+    pub fn is_constant(&self, identifier: &str) -> bool {
+        self.constants.contains(identifier)
+    }
+
+    pub fn annotate(&mut self, identifier: &str, datatype: DataType) {
+        self.annotations.insert(identifier.into(), datatype);
+    }
+
+    pub fn annotation(&self, identifier: &str) -> Option<DataType> {
+        self.annotations.get(identifier).copied()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Variable> {
+        self.slots.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn clear(&mut self) {
+        self.bytes = 0;
+        self.slots.clear();
+        self.indices.clear();
+        self.constants.clear();
+        self.annotations.clear();
+    }
+}
+
+impl IntoIterator for Variables {
+    type Item = Variable;
+    type IntoIter = std::vec::IntoIter<Variable>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.slots.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Variables {
+    type Item = &'a Variable;
+    type IntoIter = std::slice::Iter<'a, Variable>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.slots.iter()
+    }
+}
+
+impl FromIterator<Variable> for Variables {
+    fn from_iter<I: IntoIterator<Item = Variable>>(iterator: I) -> Self {
+        let mut variables = Variables::new();
+
+        for variable in iterator {
+            variables.assign(variable);
+        }
+
+        variables
+    }
+}
+
+#[test]
+fn test_variables() {
+    let mut variables: Variables = ["a", "b", "a"]
+        .iter()
+        .enumerate()
+        .map(|(index, name)| Variable {
+            datatype: DataType::Int,
+            identifier: (*name).into(),
+            value: index.to_string(),
+        })
+        .collect();
+
+    // Assigning again keeps the first position but takes the latest value
+    assert_eq!(variables.len(), 2);
+    assert_eq!(
+        variables
+            .iter()
+            .map(|variable| (variable.identifier.as_str(), variable.value.as_str()))
+            .collect::<Vec<_>>(),
+        vec![("a", "2"), ("b", "1")]
+    );
+
+    variables.assign(Variable {
+        datatype: DataType::Str,
+        identifier: "b".into(),
+        value: "x".into(),
+    });
+
+    assert_eq!(variables.get("b").unwrap().datatype, DataType::Str);
+    assert!(variables.get("c").is_none());
+    assert_eq!(variables.bytes(), "a2bx".len());
+
+    // Both scopes share the one interned name
+    let name = Name::new("a");
+    assert!(Rc::ptr_eq(
+        &name.0,
+        &variables.get("a").unwrap().identifier.0
+    ));
+
+    // Names nothing holds anymore don't stay interned forever
+    let interned = || NAMES.with(|names| names.borrow().0.len());
+
+    for index in 0..MIN_NAMES_KEPT * 4 {
+        Name::new(&format!("temporary_{}", index));
+    }
+
+    assert!(interned() <= MIN_NAMES_KEPT);
+    assert!(variables.contains("a"));
+}
@@ -0,0 +1,169 @@
+use std::path::Path;
+
+use silm::{
+    checker::check_source,
+    interpreter::{interpret_source, Context},
+    stdio::MemoryIo,
+    variables::Variables,
+};
+
+// Runs `source` as main.slm with `input` as stdin, returning the context and what it wrote
+fn run(source: &str, input: &str) -> (Context, MemoryIo) {
+    let io = MemoryIo::new(input);
+    let mut context = Context {
+        io: Box::new(io.clone()),
+        ..Default::default()
+    };
+    let mut variables: Variables = Variables::new();
+
+    interpret_source(source, "main.slm".into(), &mut variables, &mut context);
+
+    (context, io)
+}
+
+#[test]
+fn test_println() {
+    let (context, io) = run(
+        "let x = 2\nprintln (x * 3)\nformatln (\"x is {x}\")\nprintln ()",
+        "",
+    );
+
+    assert_eq!(context.errors, 0);
+    assert_eq!(io.stdout_text(), "6\nx is 2\n\n");
+    assert_eq!(io.stderr_text(), "");
+}
+
+#[test]
+fn test_readln() {
+    let (context, io) = run(
+        "let a = \"\"\nlet b = \"\"\nreadln (\"name? \", a)\nreadln (\"again? \", b)\nformatln (\"{a}, {b}.\")",
+        "bob\n",
+    );
+
+    assert_eq!(context.errors, 0);
+
+    // Nothing left to read gives an empty str
+    assert_eq!(io.stdout_text(), "name? again? bob, .\n");
+}
+
+#[test]
+fn test_blocks() {
+    let (context, io) = run(
+        "block greet :: let name = \"\" \\; readln (\"name? \", name) \\; formatln (\"hi {name}\")\ngreet ()\ngreet ()",
+        "ann\nbob\n",
+    );
+
+    assert_eq!(context.errors, 0);
+    assert_eq!(io.stdout_text(), "name? hi ann\nname? hi bob\n");
+}
+
+#[test]
+fn test_errors_and_warnings() {
+    let (context, io) = run("println (eq (1, \"1\"))\nprintln (nope)\nprintlm (1)", "");
+
+    assert_eq!(context.errors, 2);
+    assert_eq!(
+        io.stdout_text(),
+        r#"false
+error at main.slm:2:10: println: variable 'nope' unrecognised
+  |
+2 | println (nope)
+  |          ^^^^
+main.slm:3: unrecognised command: printlm (1), did you mean `println`?
+  |
+3 | printlm (1)
+  | ^^^^^^^
+"#
+    );
+    assert!(io
+        .stderr_text()
+        .starts_with("warning at main.slm:1:14: eq: comparing int with str is always false\n"));
+}
+
+#[test]
+fn test_error_carets() {
+    // The name is on the line twice, the caret goes under the one that's wrong
+    let (context, io) = run(
+        "let x = x\nprintln (eq (q, q))\nprintln (\"a\", b)\nif (true) :: println (x)",
+        "",
+    );
+
+    assert_eq!(context.errors, 4);
+    assert_eq!(
+        io.stdout_text(),
+        r#"error at main.slm:1:9: let: variable 'x' unrecognised
+  |
+1 | let x = x
+  |         ^
+error at main.slm:2:14: println: eq: variable 'q' unrecognised
+  |
+2 | println (eq (q, q))
+  |              ^
+error at main.slm:3:10: println: shunting yard algorithm: invalid token: "a",
+  |
+3 | println ("a", b)
+  |          ^^^^
+error at main.slm:4:23: println: variable 'x' unrecognised
+  |
+4 | if (true) :: println (x)
+  |                       ^
+"#
+    );
+}
+
+#[test]
+fn test_int_counter() {
+    let (context, io) = run(
+        "let i: int = 0\nwhile (ne (i, 3)) :: let i = i + 1\nprintln (typeof (i))\nlet z: int = 1 + 2\nprintln (z)\nlet i = i / 2",
+        "",
+    );
+
+    assert_eq!(context.errors, 1);
+    assert_eq!(
+        io.stdout_text(),
+        r#"int
+3
+error at main.slm:6:9: let: mismatched types: 'i' is declared as int, got float
+  |
+6 | let i = i / 2
+  |         ^^^^^
+"#
+    );
+}
+
+#[test]
+fn test_misplaced_annotations() {
+    let (context, io) = run("let q:int = 3\nconst r : int = 3\nprintln (q)", "");
+
+    assert_eq!(context.errors, 3);
+    assert_eq!(
+        io.stdout_text().lines().step_by(4).collect::<Vec<&str>>(),
+        vec![
+            "error at main.slm:1:7: let: expected a space between `:` and the type",
+            "error at main.slm:2:9: const: expected the `:` right after the name",
+            "error at main.slm:3:10: println: variable 'q' unrecognised",
+        ]
+    );
+}
+
+#[test]
+fn test_runtime_and_check_agree() {
+    for source in [
+        "let x: bogus = 1",
+        "let x: int = \"ten\"",
+        "let q:int = 3",
+        "let q : int = 3",
+    ] {
+        let (_, io) = run(source, "");
+        let diagnostics = check_source(source, Path::new("main.slm"), &[]);
+
+        assert_eq!(
+            io.stdout_text().lines().next().unwrap(),
+            format!(
+                "error at main.slm:1:{}: let: {}",
+                diagnostics[0].column + 1,
+                diagnostics[0].message
+            )
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::{thread, time::Duration};
+
+use silm::{
+    helper::get_variable,
+    interpreter::{interpret_source, Context},
+    limits::Limits,
+    stdio::MemoryIo,
+    variables::Variables,
+};
+
+fn run(source: &str, limits: Limits) -> (Context, Variables, MemoryIo) {
+    let io = MemoryIo::new("");
+    let mut context = Context {
+        io: Box::new(io.clone()),
+        limits,
+        ..Default::default()
+    };
+    let mut variables: Variables = Variables::new();
+
+    interpret_source(source, "main.slm".into(), &mut variables, &mut context);
+
+    (context, variables, io)
+}
+
+#[test]
+fn test_max_steps() {
+    let (context, variables, io) = run(
+        "let i = 0\nwhile (true) :: let i = i + 1\nprintln (\"never\")",
+        Limits {
+            max_steps: Some(100),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(context.errors, 1);
+    assert!(context.stopped);
+    assert!(io
+        .stdout_text()
+        .contains("limit exceeded: ran more than 100 lines (--max-steps)"));
+    assert!(!io.stdout_text().contains("never"));
+
+    // Every iteration is two steps, checking the condition and running the body
+    assert_eq!(get_variable("i", &variables).unwrap().value, "49");
+}
+
+#[test]
+fn test_long_while() {
+    // Used to recurse once per iteration and overflow the stack
+    let (context, variables, _) = run(
+        "let i = 0.0\nwhile (ne (i, 20000.0)) :: let i = i + 1",
+        Limits::default(),
+    );
+
+    assert_eq!(context.errors, 0);
+    assert_eq!(get_variable("i", &variables).unwrap().value, "20000");
+}
+
+#[test]
+fn test_max_depth() {
+    let (context, _, io) = run("let s = \"eval (s)\"\neval (s)", Limits::default());
+
+    assert_eq!(context.errors, 1);
+    assert!(io
+        .stdout_text()
+        .contains("eval: limit exceeded: more than 100 nested calls (--max-depth)"));
+}
+
+#[test]
+fn test_max_memory() {
+    let (context, _, io) = run(
+        "let s = \"ab\"\nwhile (true) :: let s = format (\"{s}{s}\")",
+        Limits {
+            max_memory: Some(4096),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(context.errors, 1);
+    assert!(io
+        .stdout_text()
+        .contains("limit exceeded: variables take more than 4096 bytes (--max-memory)"));
+}
+
+#[test]
+fn test_max_memory_across_scopes() {
+    let limits = Limits {
+        max_memory: Some(80),
+        ..Default::default()
+    };
+    let block = "block b :: let s = \"a string of thirty bytes here\"\n";
+
+    // Neither scope goes over on its own
+    let (context, _, _) = run(&format!("{block}b ()"), limits);
+    assert_eq!(context.errors, 0);
+    assert_eq!(context.memory, 0);
+
+    // The caller's variables are still alive while the block runs
+    let (context, _, io) = run(
+        &format!("let g = \"another string of thirty bytes\"\n{block}b ()\nprintln (\"never\")"),
+        limits,
+    );
+
+    assert_eq!(context.errors, 1);
+    assert!(io.stdout_text().contains(
+        "<block b>:1:1: let: limit exceeded: variables take more than 80 bytes (--max-memory)"
+    ));
+    assert!(!io.stdout_text().contains("never"));
+}
+
+#[test]
+fn test_timeout() {
+    let (context, _, io) = run(
+        "while (true) :: let x = 1",
+        Limits {
+            timeout: Some(Duration::from_millis(50)),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(context.errors, 1);
+    assert!(io
+        .stdout_text()
+        .contains("limit exceeded: ran for more than 50ms (--timeout)"));
+}
+
+#[test]
+fn test_interrupt() {
+    let io = MemoryIo::new("");
+    let mut context = Context {
+        io: Box::new(io.clone()),
+        ..Default::default()
+    };
+    let mut variables: Variables = Variables::new();
+
+    let interrupt = context.interrupt.clone();
+    let interrupter = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        interrupt.interrupt();
+    });
+
+    interpret_source(
+        "while (true) :: let x = 1\nprintln (\"never\")",
+        "main.slm".into(),
+        &mut variables,
+        &mut context,
+    );
+
+    interrupter.join().unwrap();
+
+    assert!(context.stopped);
+    // Caught either by the loop or by the line it runs, whichever comes next
+    assert!(io
+        .stdout_text()
+        .lines()
+        .next()
+        .unwrap()
+        .ends_with(": interrupted"));
+    assert!(!io.stdout_text().contains("never"));
+
+    // Once resumed, the same context runs again
+    context.resume();
+    interpret_source(
+        "println (\"again\")",
+        "main.slm".into(),
+        &mut variables,
+        &mut context,
+    );
+
+    assert!(io.stdout_text().ends_with("again\n"));
+}
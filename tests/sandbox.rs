@@ -0,0 +1,41 @@
+use silm::{
+    interpreter::{interpret_source, Context},
+    sandbox::{Capability, Sandbox},
+    stdio::MemoryIo,
+    variables::Variables,
+};
+
+#[test]
+fn test_sandbox() {
+    let io = MemoryIo::new("");
+    let mut context = Context {
+        io: Box::new(io.clone()),
+        sandbox: Sandbox::strict(),
+        ..Default::default()
+    };
+
+    context
+        .host
+        .function("user", |(): ()| Ok(String::from("root")));
+    context.host.require("user", Capability::Env);
+
+    let mut variables: Variables = Variables::new();
+
+    interpret_source(
+        "interpret (\"other.slm\")\nlet x = user ()\nexit ()\nprintln (\"still here\")",
+        "main.slm".into(),
+        &mut variables,
+        &mut context,
+    );
+
+    assert_eq!(context.errors, 3);
+
+    let output = io.stdout_text();
+
+    assert!(
+        output.contains("interpret: permission denied: filesystem access is disabled (--no-fs)")
+    );
+    assert!(output.contains("user: permission denied: environment access is disabled (--no-env)"));
+    assert!(output.contains("exit: permission denied: exit is disabled (--no-exit)"));
+    assert!(output.ends_with("still here\n"));
+}